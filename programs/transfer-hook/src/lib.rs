@@ -6,6 +6,11 @@
  * Chỉ các địa chỉ có trong whitelist mới có thể nhận được token.
  */
 
+// #[program] của Anchor 0.31 sinh module __private::__idl ở cấp crate, trong đó vẫn gọi
+// AccountInfo::realloc (deprecated). Module này không gắn thuộc tính được nên phải cho phép
+// ở cấp crate; mã viết tay không dùng API deprecated nào. Bỏ dòng này khi nâng cấp Anchor
+#![allow(deprecated)]
+
 // PHẦN 1: IMPORTS VÀ KHAI BÁO ID
 // RefMut cho phép truy cập và chỉnh sửa dữ liệu một cách an toàn
 // đây là một tham chiếu "mượn" có thể thay đổi giá trị (mutable borrow)
@@ -13,6 +18,8 @@ use std::cell::RefMut;
 
 // Import các module từ thư viện Anchor - framework phát triển cho Solana
 use anchor_lang::prelude::*;
+// Chuyển lamports qua System Program (dùng khi tăng kích thước account)
+use anchor_lang::system_program::{ transfer, Transfer };
 // Import các module liên quan đến Token từ SPL (Solana Program Library)
 use anchor_spl::{
     // Token-2022 là phiên bản mới của token standard với nhiều tính năng mở rộng
@@ -27,6 +34,8 @@ use anchor_spl::{
         // PodAccount: Cấu trúc "Plain Old Data" để lưu trữ dữ liệu account token
         pod::PodAccount,
     },
+    // Associated Token Program, dùng để suy ra địa chỉ account token của ví
+    associated_token::AssociatedToken,
     // Interface cho Mint và TokenAccount, hoạt động với cả token tiêu chuẩn và token-2022
     token_interface::{ Mint, TokenAccount },
 };
@@ -39,15 +48,58 @@ use spl_tlv_account_resolution::{
     // ExtraAccountMetaList: Quản lý danh sách các account bổ sung
     state::ExtraAccountMetaList,
 };
+// COption: Kiểu Option của SPL, dùng cho mint_authority của Mint
+use anchor_lang::solana_program::program_option::COption;
+// Giới hạn số bytes có thể tăng thêm cho một account trong mỗi instruction
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+// Hàm hash SHA-256, dùng để xác minh bằng chứng Merkle
+use anchor_lang::solana_program::hash::hashv;
+// Địa chỉ incinerator, nơi nhận token bị đốt
+use anchor_lang::solana_program::incinerator;
 // Import các định nghĩa từ Transfer Hook Interface
-use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_transfer_hook_interface::instruction::{
+    ExecuteInstruction,
+    InitializeExtraAccountMetaListInstruction,
+    UpdateExtraAccountMetaListInstruction,
+};
+// Trait cung cấp discriminator của các instruction trong Transfer Hook Interface
+use spl_discriminator::SplDiscriminate;
 
 // Khai báo ID của program này trên Solana blockchain
 // ID này phải khớp với địa chỉ của program khi được triển khai
 declare_id!("BmcmrHRjV2feBspwFsmWWwzNThT5o6sKM1zwoQcjKoG");
 
+// Số địa chỉ tối đa có thể trả về qua return data (giới hạn 1024 bytes,
+// trừ 4 bytes tiền tố độ dài của vector)
+pub const MAX_RETURN_DATA_KEYS: usize = (1024 - 4) / 32;
+
+// Seeds của các PDA, dùng chung cho ràng buộc #[account(seeds = ...)],
+// ExtraAccountMetaList và module client để không bị lệch nhau
+pub const WHITE_LIST_SEED: &[u8] = b"white_list";
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+pub const MEMBERSHIP_SEED: &[u8] = b"membership";
+pub const REQUIRED_MINT_SEED: &[u8] = b"required_mint";
+pub const RELEASE_SEED: &[u8] = b"release";
+pub const RECEIVING_LIMIT_SEED: &[u8] = b"recv_limit";
+pub const MERKLE_SEED: &[u8] = b"merkle";
+pub const RATE_SEED: &[u8] = b"rate";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const EPOCH_QUOTA_SEED: &[u8] = b"epoch_quota";
+pub const FAN_OUT_SEED: &[u8] = b"fan_out";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const AUDIT_SEED: &[u8] = b"audit";
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+// Seed của PDA attestation, thuộc program cấp attestation (không phải program này)
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+// Seed của PDA (thuộc program_authority) ký thay authority khi quản lý whitelist qua CPI
+pub const PROGRAM_AUTHORITY_SEED: &[u8] = b"program_authority";
+
 // PHẦN 2: ENUM LỖI
 // Định nghĩa các mã lỗi có thể xảy ra trong program
+// Mã lỗi của mỗi biến thể là 6000 + vị trí trong enum và là một phần của giao diện
+// với client: biến thể mới luôn được thêm vào cuối, không bao giờ sắp xếp lại hay xóa
+// Mọi đường lỗi của program (kể cả transfer_hook) đều trả về một biến thể có tên
+// (TransferError hoặc lỗi của Anchor), program không dùng panic!/unwrap
 #[error_code]
 pub enum TransferError {
     // Lỗi khi cố gọi hàm transfer_hook không trong ngữ cảnh chuyển token
@@ -57,326 +109,6843 @@ pub enum TransferError {
     // Lỗi khi không tìm thấy địa chỉ trong whitelist (cho hàm remove_from_whitelist)
     #[msg("Account not found in whitelist")]
     AccountNotFound,
+    // Lỗi khi chủ sở hữu account đích không nắm giữ đủ token thành viên
+    #[msg("Destination owner does not hold enough of the required membership token")]
+    InsufficientMembershipBalance,
+    // Lỗi khi tham số của lịch giải phóng không hợp lệ
+    #[msg("Invalid release schedule: duration must be positive and cliff within duration")]
+    InvalidReleaseSchedule,
+    // Lỗi khi chuyển từ escrow nhiều hơn số token đã được giải phóng
+    #[msg("Amount exceeds the tokens released by the escrow schedule")]
+    AmountNotYetReleased,
+    // Lỗi khi account đích không có trong whitelist (cho hàm transfer_hook)
+    #[msg("Account not in white list")]
+    NotWhitelisted,
+    // Lỗi khi người ký giao dịch không phải là authority của whitelist
+    #[msg("Only the authority can perform this action")]
+    Unauthorized,
+    // Lỗi khi dung lượng mới nhỏ hơn số địa chỉ hiện có trong whitelist
+    #[msg("New capacity is smaller than the current number of whitelist entries")]
+    CapacityTooSmall,
+    // Lỗi khi whitelist không còn chỗ trống trong không gian đã cấp phát
+    #[msg("White list is full, resize the account to add more entries")]
+    WhitelistFull,
+    // Lỗi khi địa chỉ đã có trong whitelist (cho hàm add_to_whitelist)
+    #[msg("Account already in whitelist")]
+    AlreadyWhitelisted,
+    // Lỗi khi người chấp nhận không phải là authority đang chờ
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    // Lỗi khi mọi giao dịch chuyển token đang bị tạm dừng
+    #[msg("Transfers are paused")]
+    TransfersPaused,
+    // Lỗi khi phía gửi không có trong whitelist (khi bật check_source)
+    #[msg("Source not in white list")]
+    SourceNotWhitelisted,
+    // Lỗi khi account đích nằm trong danh sách chặn (chế độ DenyList)
+    #[msg("Destination is in the deny list")]
+    DestinationBlacklisted,
+    // Lỗi khi số lượng chuyển vượt quá giới hạn mỗi giao dịch
+    #[msg("Transfer amount exceeds the maximum allowed per transfer")]
+    TransferAmountExceeded,
+    // Lỗi khi bộ đếm thống kê bị tràn số
+    #[msg("Counter overflow")]
+    CounterOverflow,
+    // Lỗi khi địa chỉ mới được thêm vẫn đang trong thời gian chờ kích hoạt
+    #[msg("Destination was added recently and is not yet active")]
+    AddressNotYetActive,
+    // Lỗi khi danh sách admin đã đạt số lượng tối đa
+    #[msg("Too many admins")]
+    TooManyAdmins,
+    // Lỗi khi địa chỉ đã là admin (cho hàm add_admin)
+    #[msg("Account is already an admin")]
+    AlreadyAdmin,
+    // Lỗi khi địa chỉ không phải là admin (cho hàm remove_admin)
+    #[msg("Account is not an admin")]
+    AdminNotFound,
+    // Lỗi khi tổng số token account đích nhận được vượt quá giới hạn
+    #[msg("Destination receiving cap exceeded")]
+    ReceivingCapExceeded,
+    // Lỗi khi quyền nhận token của địa chỉ đã hết hạn
+    #[msg("Whitelist entry has expired")]
+    EntryExpired,
+    // Lỗi khi thời điểm hết hạn không nằm trong tương lai
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    // Lỗi khi account nguồn và account đích là một (khi không cho phép tự chuyển)
+    #[msg("Self-transfers are not allowed")]
+    SelfTransferNotAllowed,
+    // Lỗi khi whitelist đã được khởi tạo bởi một authority khác
+    #[msg("White list is already initialized by another authority")]
+    AlreadyInitialized,
+    // Lỗi khi bằng chứng Merkle không hợp lệ hoặc chưa được xác minh với root hiện tại
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+    // Lỗi khi owner nguồn vượt quá số giao dịch cho phép trong một khoảng thời gian
+    #[msg("Too many transfers from this owner, try again later")]
+    RateLimited,
+    // Lỗi khi vị trí nằm ngoài whitelist (cho hàm remove_from_whitelist_by_index)
+    #[msg("Index out of bounds")]
+    IndexOutOfBounds,
+    // Lỗi khi địa chỉ tại vị trí chỉ định khác với địa chỉ mong đợi
+    #[msg("Account at index does not match the expected account")]
+    EntryMismatch,
+    // Lỗi khi số lượng chuyển nhỏ hơn mức tối thiểu mỗi giao dịch
+    #[msg("Transfer amount is below the minimum allowed per transfer")]
+    TransferAmountTooSmall,
+    // Lỗi khi số lượng chuyển vượt quá giới hạn theo cấp (tier) của account đích
+    #[msg("Transfer amount exceeds the limit of the destination's tier")]
+    TierLimitExceeded,
+    // Lỗi khi cấp (tier) không hợp lệ
+    #[msg("Invalid tier")]
+    InvalidTier,
+    // Lỗi khi cần ghi nhận việc xóa nhưng không truyền account nhật ký
+    #[msg("Removal audit log account is required")]
+    AuditLogRequired,
+    // Lỗi khi nhật ký xóa đã đầy
+    #[msg("Removal audit log is full, prune old records first")]
+    AuditLogFull,
+    // Lỗi khi account whitelist chưa được khởi tạo hoặc không thuộc program này
+    #[msg("White list is not initialized")]
+    UninitializedWhitelist,
+    // Lỗi khi chủ sở hữu account đích không nắm giữ token của required_mint
+    #[msg("Destination owner does not hold the required mint")]
+    MissingRequiredMint,
+    // Lỗi khi địa chỉ vừa được thêm/xóa và chưa hết thời gian chờ
+    #[msg("Address was changed recently, wait for the cooldown to end")]
+    AddressCooldown,
+    // Lỗi khi authority mới không thuộc sở hữu của program mong đợi (ví dụ multisig)
+    #[msg("New authority is not owned by the expected program")]
+    InvalidAuthorityTarget,
+    // Lỗi khi quy đổi số lượng token nguyên sang đơn vị cơ sở bị tràn số
+    #[msg("Amount overflow")]
+    AmountOverflow,
+    // Lỗi khi danh sách program được phép đã đầy
+    #[msg("Too many allowed owner programs")]
+    TooManyOwnerPrograms,
+    // Lỗi khi program đã có trong danh sách được phép
+    #[msg("Owner program is already allowed")]
+    OwnerProgramAlreadyAllowed,
+    // Lỗi khi program không có trong danh sách được phép
+    #[msg("Owner program is not allowed")]
+    OwnerProgramNotAllowed,
+    // Lỗi khi account ExtraAccountMetaList chưa được khởi tạo
+    #[msg("Extra account meta list is not initialized")]
+    MetaListNotInitialized,
+    // Lỗi khi kích thước tăng thêm vượt quá giới hạn realloc mỗi instruction
+    #[msg("Realloc limit exceeded, split the growth across multiple transactions")]
+    ReallocFailed,
+    // Lỗi khi account token đích chưa tồn tại trước giao dịch (require_existing_destination)
+    #[msg("Destination token account must exist before the transfer")]
+    DestinationMustPreExist,
+    // Lỗi khi thao tác xóa làm whitelist trở nên rỗng trong khi prevent_empty được bật
+    #[msg("White list cannot be emptied while prevent_empty is enabled")]
+    CannotEmptyWhitelist,
+    // Lỗi khi admin xác nhận địa chỉ do chính mình đề xuất (cần hai người khác nhau)
+    #[msg("The proposer cannot confirm their own addition")]
+    SameSignerNotAllowed,
+    // Lỗi khi không tìm thấy địa chỉ trong danh sách chờ xác nhận
+    #[msg("Account not found in pending additions")]
+    PendingAdditionNotFound,
+    // Lỗi khi địa chỉ đã có trong danh sách chờ xác nhận
+    #[msg("Account is already pending confirmation")]
+    AdditionAlreadyPending,
+    // Lỗi khi danh sách chờ xác nhận đã đầy
+    #[msg("Too many pending additions")]
+    TooManyPendingAdditions,
+    // Lỗi khi thao tác thêm địa chỉ trực tiếp trong khi require_confirmation được bật
+    #[msg("Additions require confirmation by a second admin")]
+    ConfirmationRequired,
+    // Lỗi khi whitelist có quá nhiều địa chỉ để lưu trong một ảnh chụp
+    #[msg("White list is too large to snapshot")]
+    SnapshotTooLarge,
+    // Lỗi khi slot của ảnh chụp không phải slot hiện tại hoặc gần đây
+    #[msg("Snapshot slot must be the current slot or a recent one")]
+    InvalidSnapshotSlot,
+    // Lỗi khi owner nguồn vượt quá số lượng được chuyển trong epoch hiện tại
+    #[msg("Source owner exceeded the transfer quota for this epoch")]
+    EpochQuotaExceeded,
+    // Lỗi khi xóa admin sẽ khiến không còn ai quản lý được whitelist
+    // (xóa authority hoặc admin cuối cùng khi authority không dùng được)
+    #[msg("Cannot remove the authority or the last remaining admin")]
+    CannotRemoveLastAdmin,
+    // Lỗi khi owner nguồn gửi đến quá nhiều địa chỉ nhận mới trong khoảng thời gian
+    #[msg("Source owner sent to too many new destinations in this window")]
+    FanOutLimitExceeded,
+    // Lỗi khi account theo dõi của account đích đã được tạo
+    #[msg("Destination record already exists")]
+    RecordAlreadyExists,
+    // Lỗi khi seeds của PDA authority vượt quá giới hạn hoặc không suy ra được PDA
+    #[msg("Invalid authority PDA seeds")]
+    InvalidAuthoritySeeds,
+    // Lỗi khi account token nguồn hoặc đích không thuộc mint của giao dịch
+    #[msg("Token account mint does not match the transfer mint")]
+    MintMismatch,
+    // Lỗi khi chủ sở hữu account đích không có attestation hợp lệ ở chế độ Attestation
+    #[msg("Destination owner has no valid attestation")]
+    AttestationRequired,
+    // Lỗi khi attestation của chủ sở hữu account đích đã hết hạn
+    #[msg("Destination owner attestation has expired")]
+    AttestationExpired,
+    // Lỗi khi địa chỉ đích đang bị tạm ngưng
+    #[msg("Address is suspended")]
+    AddressSuspended,
+    // Lỗi khi chuyển token giữa hai ví khác nhau lúc same_owner_only được bật
+    #[msg("Transfers are only allowed between accounts of the same owner")]
+    CrossOwnerTransferBlocked,
+    // Lỗi khi payer không đủ lamports để giữ account được miễn rent sau khi tăng kích thước
+    #[msg("Payer cannot cover the rent-exempt minimum for the new account size")]
+    InsufficientRentTopUp,
+    // Lỗi khi account whitelist có layout cũ, cần gọi migrate trước
+    #[msg("White list uses an older layout, call migrate first")]
+    MigrationRequired,
+    // Lỗi khi phí vượt quá MAX_FEE_BASIS_POINTS (10%)
+    #[msg("Fee exceeds the maximum of 1000 basis points")]
+    FeeTooHigh,
+    // Lỗi khi phí được bật nhưng account treasury chưa được tạo
+    #[msg("Treasury account is not initialized")]
+    TreasuryNotInitialized,
+    // Lỗi khi mẫu số phí bằng 0 hoặc tử số lớn hơn mẫu số
+    #[msg("Fee numerator must not exceed a non-zero fee denominator")]
+    InvalidFeeRate,
+    // Lỗi khi thời gian chờ kích hoạt là số âm
+    #[msg("Activation delay must not be negative")]
+    InvalidActivationDelay,
+    // Lỗi khi độ dài khoảng thời gian của giới hạn là số âm, hoặc bằng 0 khi giới hạn được bật
+    #[msg("Window length must be positive when the limit is enabled")]
+    InvalidWindow,
 }
 
 // PHẦN 3: CẤU TRÚC DỮ LIỆU CƠ BẢN
 /*
  * Định nghĩa cấu trúc dữ liệu của account whitelist
+ *
+ * Mỗi mint có một whitelist riêng (PDA từ seeds "white_list" và địa chỉ mint)
  */
 #[account]
 pub struct WhiteList {
+    // Phiên bản layout của account, luôn là byte đầu tiên sau discriminator
+    // Account được tạo trước khi có trường này (phiên bản 0) cần gọi migrate
+    pub version: u8,
     // Địa chỉ có quyền thêm/xóa địa chỉ trong whitelist
     pub authority: Pubkey,
-    // Danh sách các địa chỉ được phép (whitelist)
-    // Lưu ý: Vector này có kích thước động và giới hạn bởi kích thước
-    // của account (400 bytes đã được cấp phát)
-    // Mỗi Pubkey chiếm 32 bytes, nên whitelist có thể chứa tối đa khoảng 10-12 địa chỉ
-    // tùy thuộc vào các metadata khác
-    pub white_list: Vec<Pubkey>,
+    // Danh sách các địa chỉ ví (owner) được phép nhận token (whitelist)
+    // Mọi account token thuộc về một ví trong danh sách đều được phép nhận
+    // Lưu ý: Vector này có kích thước động và giới hạn bởi max_entries
+    // cũng như kích thước của account
+    // Mỗi phần tử gồm địa chỉ, thời điểm được thêm, thời điểm hết hạn, cấp, slot thay đổi
+    // và cờ tạm ngưng (tối đa 59 bytes)
+    // Các phần tử luôn được sắp xếp theo địa chỉ để tra cứu bằng tìm kiếm nhị phân
+    // (account cũ cần gọi upgrade_whitelist_layout để được sắp xếp lại)
+    pub white_list: Vec<WhiteListEntry>,
+    // authority mới đang chờ chấp nhận (bước 1 của việc chuyển quyền)
+    // Lưu ý: Các trường mới được đặt sau vector để các account cũ vẫn đọc được,
+    // phần dữ liệu trống (toàn 0) được hiểu là giá trị mặc định (None/false/0)
+    pub pending_authority: Option<Pubkey>,
+    // Công tắc khẩn cấp: khi bật, mọi giao dịch chuyển token đều bị chặn
+    pub paused: bool,
+    // Khi bật, phía gửi (owner hoặc account token nguồn) cũng phải có trong whitelist
+    pub check_source: bool,
+    // Chế độ của danh sách: AllowList (mặc định) hoặc DenyList
+    pub mode: WhitelistMode,
+    // Số lượng tối đa cho mỗi giao dịch chuyển token, None = không giới hạn
+    pub max_transfer_amount: Option<u64>,
+    // Tổng số giao dịch chuyển token thành công
+    pub transfer_count: u64,
+    // Tổng số lượng token đã chuyển thành công
+    pub total_volume: u128,
+    // Số giây chờ sau khi một địa chỉ được thêm trước khi nó có thể nhận token
+    pub activation_delay: i64,
+    // Số địa chỉ tối đa của whitelist, được chọn khi khởi tạo
+    // 0 = account cũ chưa có giá trị này, giới hạn chỉ theo kích thước account
+    pub max_entries: u16,
+    // Các địa chỉ được authority ủy quyền thêm/xóa địa chỉ trong whitelist
+    // Admin không thể thay đổi cấu hình khác hay quản lý danh sách admin
+    // Lưu ý: Tối đa MAX_ADMINS phần tử, không gian đã được dành sẵn trong BASE_LEN
+    pub admins: Vec<Pubkey>,
+    // Tên hiển thị của whitelist (UTF-8, phần còn lại được đệm bằng byte 0)
+    // Account cũ cần gọi upgrade_whitelist_layout để có chỗ cho trường này
+    pub name: [u8; 32],
+    // Khi bật, chặn giao dịch có account token nguồn trùng với account đích
+    // Lưu ý: Lưu dưới dạng "chặn" thay vì "cho phép" để account cũ (byte 0 = false)
+    // vẫn cho phép tự chuyển như trước
+    pub block_self_transfer: bool,
+    // Root của cây Merkle chứa các địa chỉ được phép (chế độ MerkleRoot)
+    pub merkle_root: [u8; 32],
+    // Số giao dịch tối đa mỗi owner nguồn được thực hiện trong một khoảng thời gian
+    // 0 = tắt giới hạn
+    pub max_per_window: u32,
+    // Độ dài của khoảng thời gian giới hạn (giây)
+    pub window_seconds: i64,
+    // Bump của PDA white_list và extra_account_meta_list, lưu lại để các instruction
+    // (đặc biệt là transfer_hook) không phải tính lại bằng find_program_address
+    // Account cũ cần gọi upgrade_whitelist_layout để ghi các giá trị này
+    pub white_list_bump: u8,
+    pub meta_list_bump: u8,
+    // Program được phép thêm/xóa địa chỉ qua CPI (ví dụ program quản trị DAO)
+    // mà không cần chữ ký của authority, None = tắt
+    // Program ký bằng PDA seeds ["program_authority", mint] của chính nó (invoke_signed)
+    pub program_authority: Option<Pubkey>,
+    // Số lượng tối thiểu cho mỗi giao dịch chuyển token (chặn giao dịch "bụi"), 0 = tắt
+    pub min_transfer_amount: u64,
+    // Số lượng tối đa mỗi giao dịch mà account đích thuộc cấp 0 / cấp 1 được nhận
+    // (ví dụ cấp 1 là địa chỉ "thử thách" chỉ được nhận số lượng nhỏ), 0 = không giới hạn
+    pub tier0_max: u64,
+    pub tier1_max: u64,
+    // Khi bật, mọi địa chỉ bị xóa khỏi whitelist được ghi vào nhật ký xóa
+    // (RemovalAudit) kèm thời điểm xóa thay vì bị bỏ đi hoàn toàn
+    pub record_removals: bool,
+    // Khi được đặt, chủ sở hữu account đích phải có account token của mint này
+    // với số dư khác 0 (ví dụ nắm giữ token đối tác A mới được nhận token B)
+    pub required_mint: Option<Pubkey>,
+    // Số slot tối thiểu giữa hai lần thay đổi (thêm/xóa) của cùng một địa chỉ, 0 = tắt
+    // Ngăn việc thêm/xóa liên tục để né activation_delay
+    pub cooldown_slots: u64,
+    // Các địa chỉ vừa bị xóa còn trong thời gian chờ (tối đa MAX_RECENT_REMOVALS phần tử,
+    // không gian đã được dành sẵn trong BASE_LEN, phần tử cũ nhất bị thay thế khi đầy)
+    // Lưu ý: clear_whitelist không ghi vào danh sách này
+    pub recent_removals: Vec<RecentRemoval>,
+    // Địa chỉ gửi (ví dụ treasury) được bỏ qua kiểm tra whitelist của phía nhận,
+    // dùng để phân phối token ban đầu mà không cần thêm trước từng người nhận, None = tắt
+    pub bypass_sender: Option<Pubkey>,
+    // So khớp whitelist với chủ sở hữu (mặc định) hay địa chỉ của account token đích
+    pub match_mode: MatchMode,
+    // Các program mà mọi địa chỉ (vault PDA) thuộc sở hữu của chúng đều được nhận token
+    // mà không cần có trong whitelist (ví dụ AMM, giao thức cho vay)
+    // Địa chỉ cần được đăng ký trước bằng register_program_vault
+    // Lưu ý: Tối đa MAX_OWNER_PROGRAMS phần tử, không gian đã được dành sẵn trong BASE_LEN
+    pub allowed_owner_programs: Vec<Pubkey>,
+    // Chỉ cho phép nhận vào account token đã tồn tại (có số dư) trước giao dịch,
+    // trừ khi chính account token đích có trong whitelist
+    pub require_existing_destination: bool,
+    // Khi bật, các thao tác xóa không được để whitelist rỗng (whitelist rỗng chặn mọi giao dịch)
+    // authority cần tắt cờ này trước nếu thực sự muốn xóa hết
+    pub prevent_empty: bool,
+    // Khi bật, địa chỉ được thêm bởi một admin chỉ vào danh sách chờ (pending_additions)
+    // và phải được một admin khác xác nhận bằng confirm_addition (nguyên tắc bốn mắt)
+    pub require_confirmation: bool,
+    // Các địa chỉ đang chờ xác nhận (tối đa MAX_PENDING_ADDITIONS phần tử,
+    // không gian đã được dành sẵn trong BASE_LEN)
+    pub pending_additions: Vec<PendingAddition>,
+    // Khi bật, giao dịch 0 token (ví dụ dùng cho sổ địa chỉ, thông báo) bỏ qua kiểm tra whitelist
+    // Mặc định tắt: giao dịch 0 token được kiểm tra như mọi giao dịch khác
+    pub allow_zero_amount: bool,
+    // Tổng số lượng tối đa mỗi owner nguồn được chuyển trong một epoch, 0 = tắt
+    // Khi bật, owner phải tạo bộ đếm bằng create_epoch_quota trước khi chuyển token
+    pub per_epoch_quota: u64,
+    // Số địa chỉ nhận mới tối đa mỗi owner nguồn được gửi đến trong fan_out_window_seconds giây
+    // 0 = tắt. Khi bật, owner phải tạo bộ đếm bằng create_fan_out_counter trước khi chuyển token
+    pub max_destinations_per_window: u32,
+    pub fan_out_window_seconds: i64,
+    // Khi bật, transfer_hook phát sự kiện TransferObserved cho mỗi giao dịch thành công
+    // Mặc định tắt để không tăng log và compute trên đường xử lý chính
+    pub verbose_logging: bool,
+    // Cách suy ra authority khi authority là PDA của một program quản trị (set_authority_pda)
+    // None nếu authority là địa chỉ thông thường
+    pub authority_pda: Option<AuthorityPda>,
+    // Program cấp attestation cho chế độ Attestation (PDA seeds ["attestation", owner])
+    pub attestation_program: Option<Pubkey>,
+    // Vị trí (byte) của expires_at (i64, little-endian) trong dữ liệu attestation
+    // None = attestation không có thời hạn, expires_at <= 0 cũng được coi là không hết hạn
+    pub attestation_expiry_offset: Option<u16>,
+    // Cho phép đốt token bằng cách chuyển đến địa chỉ incinerator mà không cần có trong whitelist
+    // Bật mặc định cho whitelist mới; whitelist cũ sau khi nâng cấp layout có giá trị false
+    pub allow_burn: bool,
+    // Địa chỉ được gọi update_extra_account_meta_list, None = authority của whitelist
+    pub meta_list_authority: Option<Pubkey>,
+    // Khi bật, chỉ cho phép chuyển token giữa các account của cùng một ví (tự lưu ký)
+    // và bỏ qua kiểm tra thành viên whitelist, các giới hạn số lượng vẫn được áp dụng
+    pub same_owner_only: bool,
+    // Phí giao thức tính trên mỗi giao dịch: amount * fee_numerator / fee_denominator
+    // fee_numerator = 0 tắt phí (giá trị của whitelist cũ), tỉ lệ tối đa là 10%
+    pub fee_numerator: u32,
+    // Mẫu số của phí, mặc định FEE_DENOMINATOR (basis point), ví dụ 1_000_000 cho phần triệu
+    // 0 (whitelist tạo trước khi có trường này) được hiểu là FEE_DENOMINATOR
+    pub fee_denominator: u32,
 }
 
-// PHẦN 4: CÁC CẤU TRÚC account VÀ LOGIC LIÊN QUAN
 /*
- * Định nghĩa cấu trúc account cho hàm khởi tạo ExtraAccountMetaList
- *
- * Mô tả các account cần thiết và cách chúng được xác thực
- * khi gọi hàm initialize_extra_account_meta_list
+ * Một phần tử của whitelist
  */
-#[derive(Accounts)]
-pub struct InitializeExtraAccountMetaList<'info> {
-    // Người trả phí cho việc tạo account
-    // mut: account này có thể bị trừ lamports
-    #[account(mut)]
-    payer: Signer<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WhiteListEntry {
+    // Địa chỉ ví được phép
+    pub account: Pubkey,
+    // Thời điểm được thêm vào whitelist (unix timestamp)
+    pub added_at: i64,
+    // Thời điểm hết hạn (unix timestamp), None = không bao giờ hết hạn
+    pub expires_at: Option<i64>,
+    // Cấp tin cậy của địa chỉ, quyết định giới hạn số lượng nhận (tier0_max/tier1_max)
+    // Địa chỉ mới được thêm có cấp 0
+    pub tier: u8,
+    // Slot của lần thay đổi gần nhất (lúc được thêm), dùng cho cooldown_slots
+    pub changed_slot: u64,
+    // Tạm ngưng quyền nhận token (ví dụ trong thời gian điều tra) mà vẫn giữ nguyên dữ liệu
+    pub suspended: bool,
+}
 
-    /// CHECK: ExtraAccountMetaList Account, must use these seeds
-    // account PDA lưu trữ thông tin về các account bổ sung
-    // Được tạo từ seeds bao gồm "extra-account-metas" và địa chỉ của mint
-    // Đây là account quan trọng cho Transfer Hook, Token-2022 sẽ sử dụng nó
-    // để biết cần truy xuất account bổ sung nào khi chuyển token
-    #[account(
-        init,  // Khởi tạo account mới
-        seeds = [b"extra-account-metas", mint.key().as_ref()],  // Seeds để tạo PDA
-        bump,  // Bump seed sẽ được tự động tính toán
-        space = ExtraAccountMetaList::size_of(  // Kích thước của account
-            InitializeExtraAccountMetaList::extra_account_metas()?.len()
-        )?,
-        payer = payer  // Người trả phí cho việc tạo account
-    )]
-    pub extra_account_meta_list: AccountInfo<'info>,
-    // account mint của token
-    pub mint: InterfaceAccount<'info, Mint>,
-    // System Program, cần thiết để tạo account
-    pub system_program: Program<'info, System>,
-    // account lưu trữ whitelist
-    // Được tạo từ seed "white_list"
-    // init_if_needed: Tạo mới nếu chưa tồn tại
-    // space = 400: Cấp phát 400 bytes cho account
-    // Lưu ý: Kích thước cố định này giới hạn số lượng địa chỉ có thể thêm vào whitelist
-    #[account(init_if_needed, seeds = [b"white_list"], bump, payer = payer, space = 400)]
-    pub white_list: Account<'info, WhiteList>,
+impl WhiteListEntry {
+    // Kiểm tra phần tử đã hết hạn tại thời điểm now chưa
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
 }
 
 /*
- * Định nghĩa các account bổ sung cần thiết cho Transfer Hook
- * 
- * Mô tả các account bổ sung cần được cung cấp tự động
- * khi Token-2022 gọi transfer hook
+ * Một địa chỉ vừa bị xóa khỏi whitelist, dùng cho cooldown_slots
  */
-impl<'info> InitializeExtraAccountMetaList<'info> {
-    pub fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
-        Ok(
-            vec![
-                // Chỉ có một account bổ sung là white_list
-                ExtraAccountMeta::new_with_seeds(
-                    &[
-                        // Seed để tạo PDA cho account white_list
-                        Seed::Literal {
-                            bytes: "white_list".as_bytes().to_vec(),
-                        },
-                    ],
-                    false, // is_signer: false - không yêu cầu account này là signer
-                    true // is_writable: true - account này cần có quyền ghi
-                )?
-            ]
-        )
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RecentRemoval {
+    // Địa chỉ bị xóa
+    pub account: Pubkey,
+    // Slot bị xóa
+    pub slot: u64,
+}
+
+/*
+ * Một địa chỉ đang chờ admin thứ hai xác nhận trước khi được thêm vào whitelist
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PendingAddition {
+    // Địa chỉ được đề xuất thêm
+    pub account: Pubkey,
+    // Admin đã đề xuất, không được tự xác nhận
+    pub proposer: Pubkey,
+    // Thời điểm hết hạn của địa chỉ sau khi được xác nhận, None = không hết hạn
+    pub expires_at: Option<i64>,
+}
+
+/*
+ * Thông tin để suy ra lại PDA authority của một program quản trị
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AuthorityPda {
+    // Program sở hữu PDA
+    pub program_id: Pubkey,
+    // Seeds (không gồm bump), tối đa MAX_SEEDS phần tử, mỗi phần tử tối đa MAX_SEED_LEN bytes
+    pub seeds: Vec<Vec<u8>>,
+    // Bump tìm được bằng find_program_address
+    pub bump: u8,
+}
+
+impl AuthorityPda {
+    // Số seeds tối đa được lưu (không gian đã được dành sẵn trong WhiteList::BASE_LEN)
+    pub const MAX_SEEDS: usize = 4;
+    // Độ dài tối đa của mỗi seed
+    pub const MAX_SEED_LEN: usize = 32;
+    // Kích thước tối đa khi lưu trong Option: tag (1) + program_id (32)
+    // + seeds (4 + MAX_SEEDS * (4 + MAX_SEED_LEN)) + bump (1)
+    pub const MAX_LEN: usize = 1 + 32 + 4 + Self::MAX_SEEDS * (4 + Self::MAX_SEED_LEN) + 1;
+
+    /*
+     * Suy ra PDA từ program_id và seeds
+     *
+     * Trả về InvalidAuthoritySeeds nếu seeds vượt quá giới hạn
+     * hoặc không tìm được bump hợp lệ
+     */
+    pub fn derive(program_id: Pubkey, seeds: Vec<Vec<u8>>) -> Result<(Pubkey, Self)> {
+        if seeds.len() > Self::MAX_SEEDS || seeds.iter().any(|seed| seed.len() > Self::MAX_SEED_LEN) {
+            msg!("At most {0} seeds of {1} bytes are supported", Self::MAX_SEEDS, Self::MAX_SEED_LEN);
+            return err!(TransferError::InvalidAuthoritySeeds);
+        }
+        let seed_slices: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let (address, bump) = Pubkey::try_find_program_address(&seed_slices, &program_id)
+            .ok_or(TransferError::InvalidAuthoritySeeds)?;
+        Ok((address, Self { program_id, seeds, bump }))
+    }
+
+    // Suy ra lại địa chỉ PDA từ thông tin đã lưu, dùng cho kiểm tra chữ ký qua CPI sau này
+    pub fn address(&self) -> Option<Pubkey> {
+        let bump = [self.bump];
+        let mut seed_slices: Vec<&[u8]> = self.seeds.iter().map(Vec::as_slice).collect();
+        seed_slices.push(&bump);
+        Pubkey::create_program_address(&seed_slices, &self.program_id).ok()
     }
 }
 
 /*
- * Định nghĩa cấu trúc account cho hàm Transfer Hook
- * 
- * QUAN TRỌNG: Thứ tự 4 account đầu tiên PHẢI theo đúng thứ tự:
- * source_token, mint, destination_token, owner
- * Đây là yêu cầu của Transfer Hook Interface
+ * Chế độ hoạt động của danh sách
  */
-#[derive(Accounts)]
-pub struct TransferHook<'info> {
-    // account token nguồn
-    // Phải thỏa mãn: token::mint = mint, token::authority = owner
-    #[account(token::mint = mint, token::authority = owner)]
-    pub source_token: InterfaceAccount<'info, TokenAccount>,
-    // account mint của token
-    pub mint: InterfaceAccount<'info, Mint>,
-    // account token đích
-    // Phải thỏa mãn: token::mint = mint
-    #[account(token::mint = mint)]
-    pub destination_token: InterfaceAccount<'info, TokenAccount>,
-    /// CHECK: source token account owner, can be SystemAccount or PDA owned by another program
-    // Chủ sở hữu của account nguồn
-    pub owner: UncheckedAccount<'info>,
-    /// CHECK: ExtraAccountMetaList Account,
-    // account lưu trữ thông tin về các account bổ sung
-    // Được xác định bằng PDA từ seed "extra-account-metas" và địa chỉ mint
-    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
-    pub extra_account_meta_list: UncheckedAccount<'info>,
-    // account lưu trữ whitelist
-    // Được xác định bằng PDA từ seed "white_list"
-    #[account(seeds = [b"white_list"], bump)]
-    pub white_list: Account<'info, WhiteList>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WhitelistMode {
+    // Chỉ các địa chỉ có trong danh sách mới được nhận token (mặc định)
+    AllowList,
+    // Mọi địa chỉ đều được nhận token, trừ các địa chỉ có trong danh sách
+    DenyList,
+    // Chỉ các địa chỉ thuộc cây Merkle có root là merkle_root mới được nhận token
+    // Dùng cho danh sách rất lớn không thể lưu trực tiếp trong account
+    MerkleRoot,
+    // Chỉ các địa chỉ có attestation (ví dụ chứng nhận KYC) còn hiệu lực do
+    // attestation_program cấp mới được nhận token, không dùng danh sách
+    Attestation,
 }
 
 /*
- * Định nghĩa cấu trúc account cho hàm thêm vào whitelist
+ * Địa chỉ phía nhận được so khớp với whitelist
  */
-#[derive(Accounts)]
-pub struct AddToWhiteList<'info> {
-    /// CHECK: New account to add to white list
-    // Địa chỉ mới cần thêm vào whitelist
-    // Không cần kiểm tra gì về account này
-    #[account()]
-    pub new_account: AccountInfo<'info>,
-    // account whitelist, cần có quyền ghi để cập nhật
-    // mut: account này sẽ bị chỉnh sửa (thêm địa chỉ mới)
-    #[account(
-        mut,
-        seeds = [b"white_list"],
-        bump
-    )]
-    pub white_list: Account<'info, WhiteList>,
-    // Người ký giao dịch, phải là authority của whitelist
-    // mut: account này sẽ trả phí giao dịch
-    #[account(mut)]
-    pub signer: Signer<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchMode {
+    // Chủ sở hữu (ví) của account token đích (mặc định, giữ nguyên hành vi trước đây)
+    // Lưu ý: Phải là biến thể đầu tiên để account cũ (byte 0) vẫn dùng chế độ này
+    Owner,
+    // Địa chỉ của chính account token đích
+    TokenAccount,
 }
 
 /*
- * Định nghĩa cấu trúc account cho hàm xóa khỏi whitelist
+ * Các vi phạm cấu hình mà health_check có thể phát hiện
+ *
+ * Được trả về qua return data để client đọc bằng simulateTransaction
  */
-#[derive(Accounts)]
-pub struct RemoveFromWhiteList<'info> {
-    /// CHECK: Account to remove from white list
-    // Địa chỉ cần xóa khỏi whitelist
-    // Không cần kiểm tra gì về account này
-    #[account()]
-    pub account_to_remove: AccountInfo<'info>,
-    // account whitelist, cần có quyền ghi để cập nhật
-    // mut: account này sẽ bị chỉnh sửa (xóa địa chỉ)
-    #[account(
-        mut,
-        seeds = [b"white_list"],
-        bump
-    )]
-    pub white_list: Account<'info, WhiteList>,
-    // Người ký giao dịch, phải là authority của whitelist
-    // mut: account này sẽ trả phí giao dịch
-    #[account(mut)]
-    pub signer: Signer<'info>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigViolation {
+    // authority vẫn là Pubkey mặc định (account chưa được khởi tạo đúng cách)
+    UninitializedAuthority,
+    // Một địa chỉ xuất hiện nhiều lần trong whitelist
+    DuplicateEntry,
+    // Dữ liệu whitelist vượt quá kích thước đã cấp phát cho account
+    ExceedsAllocatedSpace,
+    // Số lượng tối thiểu lớn hơn số lượng tối đa, mọi giao dịch đều bị chặn
+    MinAboveMax,
+    // Whitelist chưa được sắp xếp theo địa chỉ, cần gọi upgrade_whitelist_layout
+    UnsortedEntries,
+    // Tỉ lệ phí đang lưu không qua được validate_fee (tử số lớn hơn mẫu số hoặc vượt quá 10%)
+    InvalidFeeRate,
 }
 
-// PHẦN 5: MODULE CHƯƠNG TRÌNH CHÍNH
-// Định nghĩa các hàm xử lý (entry points) của smart contract
-#[program]
-pub mod transfer_hook {
-    use super::*;
+/*
+ * Ảnh chụp toàn bộ cấu hình của whitelist, được trả về bởi get_config
+ *
+ * Được trả về qua return data để client không phụ thuộc vào layout của account
+ * Lưu ý: Khi thêm trường cấu hình mới vào WhiteList, cần thêm vào đây và WhiteList::config
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct WhitelistConfig {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub program_authority: Option<Pubkey>,
+    pub authority_pda: Option<AuthorityPda>,
+    pub meta_list_authority: Option<Pubkey>,
+    pub admins: Vec<Pubkey>,
+    pub paused: bool,
+    pub mode: WhitelistMode,
+    pub match_mode: MatchMode,
+    pub check_source: bool,
+    pub block_self_transfer: bool,
+    pub require_existing_destination: bool,
+    pub record_removals: bool,
+    pub prevent_empty: bool,
+    pub require_confirmation: bool,
+    pub allow_zero_amount: bool,
+    pub allow_burn: bool,
+    pub same_owner_only: bool,
+    pub verbose_logging: bool,
+    // Số địa chỉ đang chờ xác nhận (danh sách đầy đủ không vừa return data cùng các trường khác)
+    pub pending_additions: u32,
+    // Số địa chỉ hiện có và số địa chỉ tối đa account có thể chứa
+    pub length: u32,
+    pub capacity: u32,
+    pub max_entries: u16,
+    pub max_transfer_amount: Option<u64>,
+    pub min_transfer_amount: u64,
+    pub tier0_max: u64,
+    pub tier1_max: u64,
+    pub activation_delay: i64,
+    pub cooldown_slots: u64,
+    pub max_per_window: u32,
+    pub window_seconds: i64,
+    pub per_epoch_quota: u64,
+    pub max_destinations_per_window: u32,
+    pub fan_out_window_seconds: i64,
+    pub required_mint: Option<Pubkey>,
+    pub bypass_sender: Option<Pubkey>,
+    pub allowed_owner_programs: Vec<Pubkey>,
+    pub merkle_root: [u8; 32],
+    pub attestation_program: Option<Pubkey>,
+    pub attestation_expiry_offset: Option<u16>,
+    pub fee_numerator: u32,
+    pub fee_denominator: u32,
+}
 
-    /*
-     * Hàm khởi tạo ExtraAccountMetaList
-     * 
-     * Đây là hàm bắt buộc phải triển khai theo Transfer Hook Interface
-     * Mục đích: Tạo và khởi tạo account ExtraAccountMetaList chứa thông tin
-     * về các account bổ sung cần được cung cấp khi thực hiện chuyển token
-     * 
-     * QUAN TRỌNG: Hàm này phải được gọi trước khi có thể sử dụng transfer hook
-     */
-    #[interface(spl_transfer_hook_interface::initialize_extra_account_meta_list)]
-    pub fn initialize_extra_account_meta_list(
-        ctx: Context<InitializeExtraAccountMetaList>
-    ) -> Result<()> {
-        // Thiết lập quyền sở hữu (authority) của whitelist là người trả phí (payer)
-        // Điều này xác định ai có quyền thêm/xóa địa chỉ trong whitelist
-        ctx.accounts.white_list.authority = ctx.accounts.payer.key();
+impl WhiteList {
+    // Kích thước phần cố định: discriminator (8) + version (1) + authority (32) + tiền tố độ dài vector (4)
+    // + pending_authority (33) + paused (1) + check_source (1) + mode (1)
+    // + max_transfer_amount (9) + transfer_count (8) + total_volume (16) + activation_delay (8)
+    // + max_entries (2) + admins (4 + MAX_ADMINS * 32) + name (32) + block_self_transfer (1)
+    // + merkle_root (32) + max_per_window (4) + window_seconds (8)
+    // + white_list_bump (1) + meta_list_bump (1) + program_authority (33) + min_transfer_amount (8)
+    // + tier0_max (8) + tier1_max (8) + record_removals (1) + required_mint (33)
+    // + cooldown_slots (8) + recent_removals (4 + MAX_RECENT_REMOVALS * 40) + bypass_sender (33)
+    // + match_mode (1) + allowed_owner_programs (4 + MAX_OWNER_PROGRAMS * 32)
+    // + require_existing_destination (1) + prevent_empty (1) + require_confirmation (1)
+    // + pending_additions (4 + MAX_PENDING_ADDITIONS * 73) + allow_zero_amount (1) + per_epoch_quota (8)
+    // + max_destinations_per_window (4) + fan_out_window_seconds (8) + verbose_logging (1)
+    // + authority_pda (AuthorityPda::MAX_LEN) + attestation_program (33) + attestation_expiry_offset (3)
+    // + allow_burn (1) + meta_list_authority (33) + same_owner_only (1)
+    // + fee_numerator (4) + fee_denominator (4)
+    pub const BASE_LEN: usize =
+        8 + 1 + 32 + 4 + 33 + 1 + 1 + 1 + 9 + 8 + 16 + 8 + 2 + 4 + Self::MAX_ADMINS * 32 + 32 + 1 + 32 + 4 + 8 + 1 + 1 + 33 + 8
+        + 8 + 8 + 1 + 33 + 8 + 4 + Self::MAX_RECENT_REMOVALS * 40 + 33 + 1 + 4 + Self::MAX_OWNER_PROGRAMS * 32 + 1 + 1
+        + 1 + 4 + Self::MAX_PENDING_ADDITIONS * 73 + 1 + 8 + 4 + 8 + 1 + AuthorityPda::MAX_LEN + 33 + 3 + 1 + 33 + 1 + 4 + 4;
+    // Số admin tối đa
+    pub const MAX_ADMINS: usize = 5;
+    // Số địa chỉ vừa bị xóa tối đa được lưu cho cooldown_slots
+    pub const MAX_RECENT_REMOVALS: usize = 8;
+    // Số program được phép tối đa
+    pub const MAX_OWNER_PROGRAMS: usize = 5;
+    // Số địa chỉ chờ xác nhận tối đa
+    pub const MAX_PENDING_ADDITIONS: usize = 8;
+    // Phí tối đa (10%) và mẫu số của basis point
+    pub const MAX_FEE_BASIS_POINTS: u16 = 1000;
+    pub const FEE_DENOMINATOR: u32 = 10_000;
+    // Phiên bản layout hiện tại, tăng lên khi layout thay đổi theo cách account cũ không đọc được
+    // Phiên bản 1: có byte version sau discriminator
+    // Phiên bản 2: thêm fee_numerator và fee_denominator (BASE_LEN tăng 8 bytes)
+    pub const VERSION: u8 = 2;
+    // Vị trí tiền tố độ dài vector white_list trong dữ liệu account
+    pub const WHITE_LIST_LEN_OFFSET: usize = 8 + 1 + 32;
+    // Mỗi phần tử của whitelist: Pubkey (32) + added_at (8) + expires_at (tối đa 9) + tier (1)
+    // + changed_slot (8) + suspended (1)
+    // Luôn tính theo kích thước tối đa để dung lượng không phụ thuộc vào expires_at
+    // Ví dụ: 400 bytes sau phần cố định chứa được đúng 400 / 59 = 6 địa chỉ,
+    // địa chỉ thứ 7 trả về WhitelistFull
+    pub const ENTRY_LEN: usize = 32 + 8 + 9 + 1 + 8 + 1;
+    // Số cấp tin cậy (tier) được hỗ trợ
+    pub const TIER_COUNT: u8 = 2;
 
-        // Lấy danh sách các account bổ sung cần thiết cho transfer hook
-        // Trong trường hợp này, chỉ có một account bổ sung là white_list
-        let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
+    // Số bytes cần thiết để lưu whitelist chứa tối đa capacity địa chỉ
+    pub fn space_for(capacity: usize) -> usize {
+        Self::BASE_LEN + capacity * Self::ENTRY_LEN
+    }
 
-        // Khởi tạo account ExtraAccountMetaList với danh sách các account bổ sung
-        // Token-2022 sẽ sử dụng account này để biết cần truy xuất account bổ sung nào
-        // khi thực hiện chuyển token
-        ExtraAccountMetaList::init::<ExecuteInstruction>(
-            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
-            &extra_account_metas
-        )?;
-        Ok(())
+    // Số địa chỉ tối đa có thể lưu trong account có kích thước data_len
+    pub fn capacity_for(data_len: usize) -> usize {
+        data_len.saturating_sub(Self::BASE_LEN) / Self::ENTRY_LEN
+    }
+
+    // Số địa chỉ tối đa whitelist được phép chứa: max_entries nhưng không vượt quá
+    // dung lượng thực tế của account có kích thước data_len
+    // và giá trị lớn nhất của tiền tố độ dài u32 của vector
+    pub fn capacity(&self, data_len: usize) -> usize {
+        let capacity = Self::capacity_for(data_len).min(u32::MAX as usize);
+        match self.max_entries {
+            0 => capacity,
+            max_entries => capacity.min(max_entries as usize),
+        }
+    }
+
+    // Số địa chỉ còn có thể thêm vào account có kích thước data_len, 0 khi đã đầy
+    pub fn remaining_capacity(&self, data_len: usize) -> u32 {
+        self.capacity(data_len)
+            .saturating_sub(self.white_list.len())
+            .min(u32::MAX as usize) as u32
     }
 
     /*
-     * Hàm Transfer Hook chính
-     * 
-     * Đây là hàm bắt buộc phải triển khai theo Transfer Hook Interface
-     * Mục đích: Được Token-2022 tự động gọi khi có lệnh chuyển token
-     * 
-     * QUAN TRỌNG: Hàm này được gọi tự động, không cần gọi trực tiếp
-     * Hàm này quyết định việc chuyển token có thành công hay không
+     * Thêm một phần tử vào whitelist nếu còn chỗ
+     *
+     * Phần tử được chèn theo thứ tự địa chỉ (O(n) do dịch chuyển) để việc tra cứu
+     * trong transfer_hook, được gọi thường xuyên hơn nhiều, chỉ tốn O(log n)
+     * capacity là kết quả của capacity(data_len), được tính trước bởi hàm gọi
+     * Trả về lỗi WhitelistFull (kèm log độ dài hiện tại và giới hạn) thay vì
+     * để giao dịch thất bại khi Anchor ghi dữ liệu vượt quá kích thước account
      */
-    #[interface(spl_transfer_hook_interface::execute)]
-    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
-        // Kiểm tra xem hàm có được gọi trong ngữ cảnh chuyển token không
-        // Đây là biện pháp bảo mật quan trọng để ngăn chặn việc gọi trực tiếp vào hàm này
-        check_is_transferring(&ctx)?;
+    pub fn push_entry(&mut self, entry: WhiteListEntry, capacity: usize) -> Result<()> {
+        self.ensure_room(1, capacity)?;
+        // Chèn vào đúng vị trí để whitelist luôn được sắp xếp
+        match self.white_list.binary_search_by(|other| other.account.cmp(&entry.account)) {
+            Ok(_) => err!(TransferError::AlreadyWhitelisted),
+            Err(index) => {
+                self.white_list.insert(index, entry);
+                Ok(())
+            }
+        }
+    }
 
-        // Kiểm tra xem địa chỉ đích có trong whitelist không
-        // Nếu không có thì dừng giao dịch (transaction sẽ thất bại)
-        if !ctx.accounts.white_list.white_list.contains(&ctx.accounts.destination_token.key()) {
-            panic!("Account not in white list!");
+    /*
+     * Thêm một địa chỉ chưa có vào whitelist
+     *
+     * Trả về AlreadyWhitelisted nếu địa chỉ đã có (trùng lặp lãng phí dung lượng
+     * và khiến remove_from_whitelist để sót lại một bản), WhitelistFull nếu hết chỗ
+     */
+    pub fn add_entry(&mut self, entry: WhiteListEntry, capacity: usize) -> Result<()> {
+        if self.contains(&entry.account) {
+            msg!("Account already in white list: {0}", entry.account.to_string());
+            return err!(TransferError::AlreadyWhitelisted);
         }
+        self.push_entry(entry, capacity)
+    }
 
-        // Log thông báo thành công nếu account đích nằm trong whitelist
-        msg!("Account in white list, all good!");
+    /*
+     * Xóa một địa chỉ khỏi whitelist tại slot hiện tại
+     *
+     * Trả về AccountNotFound nếu địa chỉ không có trong whitelist (kể cả khi whitelist rỗng,
+     * để giữ nguyên mã lỗi cho client cũ; log ghi rõ hai trường hợp),
+     * AddressCooldown nếu địa chỉ vừa được thêm trong vòng cooldown_slots
+     * remove() dịch chuyển các phần tử phía sau lên để giữ nguyên thứ tự
+     */
+    pub fn remove_account(&mut self, account: &Pubkey, slot: u64) -> Result<()> {
+        match self.position(account) {
+            Some(index) => {
+                self.check_cooldown(account, slot)?;
+                self.white_list.remove(index);
+                self.note_removal(*account, slot);
+                Ok(())
+            }
+            None => {
+                if self.white_list.is_empty() {
+                    msg!("White list is empty, account not found: {0}", account.to_string());
+                } else {
+                    msg!(
+                        "Account not found in whitelist: {0}, white list length: {1}",
+                        account.to_string(),
+                        self.white_list.len()
+                    );
+                }
+                err!(TransferError::AccountNotFound)
+            }
+        }
+    }
 
+    /*
+     * Đưa một địa chỉ vào danh sách chờ xác nhận
+     *
+     * Trả về AlreadyWhitelisted nếu địa chỉ đã có trong whitelist, AdditionAlreadyPending
+     * nếu đã đang chờ, TooManyPendingAdditions nếu danh sách chờ đã đầy
+     */
+    pub fn propose_addition(&mut self, pending: PendingAddition) -> Result<()> {
+        if self.contains(&pending.account) {
+            msg!("Account already in white list: {0}", pending.account.to_string());
+            return err!(TransferError::AlreadyWhitelisted);
+        }
+        if self.pending_additions.iter().any(|other| other.account == pending.account) {
+            msg!("Account already pending: {0}", pending.account.to_string());
+            return err!(TransferError::AdditionAlreadyPending);
+        }
+        if self.pending_additions.len() >= Self::MAX_PENDING_ADDITIONS {
+            return err!(TransferError::TooManyPendingAdditions);
+        }
+        self.pending_additions.push(pending);
         Ok(())
     }
 
     /*
-     * Hàm thêm địa chỉ vào whitelist
-     * 
-     * Mục đích: Cho phép authority thêm một địa chỉ mới vào whitelist
-     * Sau khi thêm, địa chỉ này sẽ có thể nhận được token thông qua chuyển token
+     * Lấy một địa chỉ ra khỏi danh sách chờ để xác nhận
+     *
+     * confirmer phải khác admin đã đề xuất, trả về SameSignerNotAllowed nếu trùng
+     * và PendingAdditionNotFound nếu địa chỉ không đang chờ
      */
-    pub fn add_to_whitelist(ctx: Context<AddToWhiteList>) -> Result<()> {
-        // Kiểm tra xem người ký giao dịch có phải là authority của whitelist không
-        // Đây là biện pháp bảo mật để đảm bảo chỉ authority mới có thể thay đổi whitelist
-        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
-            panic!("Only the authority can add to the white list!");
+    pub fn take_pending_addition(&mut self, account: &Pubkey, confirmer: &Pubkey) -> Result<PendingAddition> {
+        match self.pending_additions.iter().position(|pending| pending.account == *account) {
+            Some(index) => {
+                if self.pending_additions[index].proposer == *confirmer {
+                    return err!(TransferError::SameSignerNotAllowed);
+                }
+                Ok(self.pending_additions.remove(index))
+            }
+            None => {
+                msg!("Account not pending: {0}", account.to_string());
+                err!(TransferError::PendingAdditionNotFound)
+            }
         }
+    }
 
-        // Thêm địa chỉ mới vào whitelist
-        ctx.accounts.white_list.white_list.push(ctx.accounts.new_account.key());
-        // Log thông tin về địa chỉ đã thêm
-        msg!("New account white listed! {0}", ctx.accounts.new_account.key().to_string());
-        // Log số lượng địa chỉ hiện có trong whitelist
-        msg!("White list length! {0}", ctx.accounts.white_list.white_list.len());
-
+    // Từ chối trạng thái whitelist rỗng sau một thao tác xóa nếu prevent_empty được bật
+    pub fn check_not_empty(&self) -> Result<()> {
+        if self.prevent_empty && self.white_list.is_empty() {
+            msg!("Refusing to leave the white list empty, disable prevent_empty first");
+            return err!(TransferError::CannotEmptyWhitelist);
+        }
         Ok(())
     }
 
     /*
-     * Hàm xóa địa chỉ khỏi whitelist
-     * 
-     * Mục đích: Cho phép authority xóa một địa chỉ khỏi whitelist
-     * Sau khi xóa, địa chỉ này không thể nhận được token thông qua chuyển token
+     * Thay thế toàn bộ whitelist bằng danh sách accounts (đã loại bỏ trùng lặp)
+     *
+     * Địa chỉ vẫn còn trong danh sách mới giữ nguyên phần tử cũ (thời điểm thêm,
+     * hạn, cấp) để activation_delay không bị đặt lại, địa chỉ mới dùng added_at/slot
+     * Trả về các địa chỉ bị loại khỏi whitelist, WhitelistFull nếu vượt quá capacity
      */
-    pub fn remove_from_whitelist(ctx: Context<RemoveFromWhiteList>) -> Result<()> {
-        // Kiểm tra xem người ký giao dịch có phải là authority của whitelist không
-        // Đây là biện pháp bảo mật để đảm bảo chỉ authority mới có thể thay đổi whitelist
-        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
-            panic!("Only the authority can remove from the white list!");
+    pub fn replace_entries(
+        &mut self,
+        accounts: &[Pubkey],
+        added_at: i64,
+        slot: u64,
+        capacity: usize
+    ) -> Result<Vec<Pubkey>> {
+        let mut entries: Vec<WhiteListEntry> = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            if entries.iter().any(|entry| entry.account == *account) {
+                continue;
+            }
+            let entry = self.entry(account).copied().unwrap_or(WhiteListEntry {
+                account: *account,
+                added_at,
+                expires_at: None,
+                tier: 0,
+                changed_slot: slot,
+                suspended: false,
+            });
+            entries.push(entry);
         }
+        if entries.len() > capacity {
+            msg!(
+                "White list is full: {0} entries requested, capacity {1}, call resize_whitelist to add more",
+                entries.len(),
+                capacity
+            );
+            return err!(TransferError::WhitelistFull);
+        }
+        entries.sort_by_key(|entry| entry.account);
 
-        // Lấy địa chỉ cần xóa
-        let account_key = ctx.accounts.account_to_remove.key();
-        // Tìm vị trí của địa chỉ trong whitelist
-        // Sử dụng hàm position để tìm chỉ số của phần tử trong vector
-        let position = ctx.accounts.white_list.white_list.iter().position(|x| *x == account_key);
-        
-        // Xử lý tùy theo kết quả tìm kiếm
-        match position {
-            // Nếu tìm thấy địa chỉ trong whitelist (Some chứa chỉ số)
-            Some(index) => {
-                // Xóa địa chỉ khỏi whitelist
-                // remove() sẽ dịch chuyển các phần tử phía sau lên để duy trì tính liên tục của vector
-                ctx.accounts.white_list.white_list.remove(index);
-                // Log thông tin về địa chỉ đã xóa
-                msg!("Account removed from whitelist: {0}", account_key.to_string());
-                // Log số lượng địa chỉ còn lại trong whitelist
-                msg!("White list length: {0}", ctx.accounts.white_list.white_list.len());
-                Ok(())
-            },
-            // Nếu không tìm thấy địa chỉ trong whitelist (None)
-            None => {
-                // Log thông báo lỗi
-                msg!("Account not found in whitelist: {0}", account_key.to_string());
-                // Trả về lỗi AccountNotFound
-                // Sử dụng err! macro để trả về lỗi một cách an toàn
-                err!(TransferError::AccountNotFound)
+        let removed = self.white_list
+            .iter()
+            .filter(|entry| !accounts.contains(&entry.account))
+            .map(|entry| entry.account)
+            .collect();
+        self.white_list = entries;
+        Ok(removed)
+    }
+
+    /*
+     * Kiểm tra whitelist còn chỗ cho thêm additional phần tử
+     *
+     * Độ dài sau khi thêm được tính bằng checked_add nên đầu vào quá lớn
+     * cũng trả về WhitelistFull thay vì tràn số
+     */
+    pub fn ensure_room(&self, additional: usize, capacity: usize) -> Result<()> {
+        match self.white_list.len().checked_add(additional) {
+            Some(len) if len <= capacity => Ok(()),
+            _ => {
+                msg!(
+                    "White list is full: {0}/{1} entries, cannot add {2}, call resize_whitelist to add more",
+                    self.white_list.len(),
+                    capacity,
+                    additional
+                );
+                err!(TransferError::WhitelistFull)
             }
         }
     }
-}
 
-// PHẦN 6: HÀM HELPER
-/*
- * Hàm kiểm tra trạng thái chuyển token
- * 
- * Đây là hàm helper để đảm bảo rằng hàm transfer_hook
- * chỉ được gọi bởi Token-2022 trong ngữ cảnh chuyển token
- * Đây là một biện pháp bảo mật quan trọng
- */
-fn check_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
-    // Lấy thông tin account token nguồn
-    let source_token_info = ctx.accounts.source_token.to_account_info();
-    // Mượn dữ liệu của account để đọc và chỉnh sửa
-    let mut account_data_ref: RefMut<&mut [u8]> = source_token_info.try_borrow_mut_data()?;
-    // Giải mã dữ liệu account thành cấu trúc PodAccount
-    // PodStateWithExtensionsMut cho phép truy cập vào dữ liệu của account
-    // và các extension của nó
-    let mut account = PodStateWithExtensionsMut::<PodAccount>::unpack(*account_data_ref)?;
-    // Lấy extension TransferHookAccount từ account
-    // TransferHookAccount chứa trạng thái của quá trình chuyển token
-    let account_extension = account.get_extension_mut::<TransferHookAccount>()?;
+    /*
+     * Kích thước mới của account có kích thước data_len sau khi thêm chỗ cho
+     * additional_entries địa chỉ
+     *
+     * Trả về ReallocFailed nếu phần tăng thêm vượt quá MAX_PERMITTED_DATA_INCREASE
+     * (giới hạn của runtime cho mỗi instruction)
+     */
+    pub fn grown_len(data_len: usize, additional_entries: u16) -> Result<usize> {
+        let new_len = Self::space_for(Self::capacity_for(data_len) + additional_entries as usize).max(data_len);
+        let increase = new_len - data_len;
+        if increase > MAX_PERMITTED_DATA_INCREASE {
+            msg!(
+                "Growing by {0} bytes exceeds the {1} bytes realloc limit, split the growth across multiple transactions",
+                increase,
+                MAX_PERMITTED_DATA_INCREASE
+            );
+            return err!(TransferError::ReallocFailed);
+        }
+        Ok(new_len)
+    }
 
-    // Kiểm tra trạng thái "transferring"
+    // Số bytes cần thiết để lưu trạng thái hiện tại của account
+    pub fn serialized_len(&self) -> usize {
+        Self::space_for(self.white_list.len())
+    }
+
+    // Vị trí của một địa chỉ trong whitelist (tìm kiếm nhị phân, O(log n))
+    pub fn position(&self, account: &Pubkey) -> Option<usize> {
+        sorted_position(&self.white_list, account)
+    }
+
+    // Kiểm tra các phần tử được sắp xếp tăng dần theo địa chỉ
+    pub fn is_sorted(&self) -> bool {
+        self.white_list.windows(2).all(|pair| pair[0].account < pair[1].account)
+    }
+
+    /*
+     * Sắp xếp lại các phần tử theo địa chỉ và bỏ các địa chỉ bị lặp
+     * (cho account tạo trước khi whitelist được sắp xếp)
+     *
+     * sort_by_key giữ nguyên thứ tự các phần tử bằng nhau nên phần tử được thêm trước được giữ lại
+     * Trả về số phần tử bị lặp đã bỏ
+     */
+    pub fn sort_entries(&mut self) -> usize {
+        let len = self.white_list.len();
+        self.white_list.sort_by_key(|entry| entry.account);
+        self.white_list.dedup_by_key(|entry| entry.account);
+        len - self.white_list.len()
+    }
+
+    // Phần tử của whitelist ứng với một địa chỉ
+    pub fn entry(&self, account: &Pubkey) -> Option<&WhiteListEntry> {
+        self.position(account).map(|index| &self.white_list[index])
+    }
+
+    /*
+     * Một trang của whitelist: tối đa limit địa chỉ bắt đầu từ vị trí offset
+     *
+     * Phạm vi được giới hạn trong vector (offset vượt quá cuối trả về trang rỗng)
+     * và trong MAX_RETURN_DATA_KEYS để vừa return data
+     */
+    pub fn page(&self, offset: u32, limit: u32) -> Vec<Pubkey> {
+        let limit = (limit as usize).min(MAX_RETURN_DATA_KEYS);
+        self.white_list
+            .iter()
+            .skip(offset as usize)
+            .take(limit)
+            .map(|entry| entry.account)
+            .collect()
+    }
+
+    // Kiểm tra một địa chỉ có trong whitelist không
+    pub fn contains(&self, account: &Pubkey) -> bool {
+        is_whitelisted_fast(&self.white_list, account)
+    }
+
+    /*
+     * Yêu cầu một địa chỉ có trong whitelist, không bị tạm ngưng và chưa hết hạn
+     *
+     * Trả về NotWhitelisted nếu không thỏa mãn, dùng cho check_membership
+     */
+    pub fn require_member(&self, account: &Pubkey, now: i64) -> Result<()> {
+        match self.entry(account) {
+            Some(entry) if !entry.suspended && !entry.is_expired(now) => Ok(()),
+            _ => {
+                msg!("Account not in white list: {0}", account.to_string());
+                err!(TransferError::NotWhitelisted)
+            }
+        }
+    }
+
+    /*
+     * Tạm ngưng hoặc khôi phục quyền nhận token của một địa chỉ
+     *
+     * Các trường khác của phần tử (cấp, thời hạn, thời điểm thêm) được giữ nguyên
+     * Trả về AccountNotFound nếu địa chỉ không có trong whitelist
+     */
+    pub fn set_suspended(&mut self, account: &Pubkey, suspended: bool) -> Result<()> {
+        match self.position(account) {
+            Some(index) => {
+                self.white_list[index].suspended = suspended;
+                Ok(())
+            }
+            None => {
+                msg!("Account not found in whitelist: {0}", account.to_string());
+                err!(TransferError::AccountNotFound)
+            }
+        }
+    }
+
+    /*
+     * Kiểm tra attestation của chủ sở hữu account đích
+     *
+     * attestation phải là PDA ["attestation", owner] của attestation_program, thuộc sở hữu
+     * của program đó và có dữ liệu. Nếu attestation_expiry_offset được cấu hình thì
+     * expires_at đọc tại vị trí đó phải còn hiệu lực
+     * Trả về AttestationRequired hoặc AttestationExpired nếu không thỏa mãn
+     */
+    pub fn check_attestation(&self, attestation: &AccountInfo, owner: &Pubkey, now: i64) -> Result<()> {
+        let Some(program) = self.attestation_program else {
+            msg!("Attestation program is not configured");
+            return err!(TransferError::AttestationRequired);
+        };
+        let (expected, _) = Pubkey::find_program_address(&[ATTESTATION_SEED, owner.as_ref()], &program);
+        if attestation.key != &expected || attestation.owner != &program || attestation.data_is_empty() {
+            msg!("No attestation for {0}", owner.to_string());
+            return err!(TransferError::AttestationRequired);
+        }
+
+        if let Some(offset) = self.attestation_expiry_offset {
+            let offset = offset as usize;
+            let data = attestation.try_borrow_data()?;
+            let expires_at = data
+                .get(offset..offset + 8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(i64::from_le_bytes)
+                .ok_or(TransferError::AttestationRequired)?;
+            if expires_at > 0 && now >= expires_at {
+                msg!("Attestation of {0} expired at {1}", owner.to_string(), expires_at);
+                return err!(TransferError::AttestationExpired);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Khởi tạo whitelist mới với authority và giới hạn số địa chỉ
+    // Bump được lưu để các instruction sau không phải tính lại
+    pub fn initialize(&mut self, authority: Pubkey, max_entries: u16, white_list_bump: u8, meta_list_bump: u8) {
+        self.version = Self::VERSION;
+        self.authority = authority;
+        self.max_entries = max_entries;
+        self.white_list_bump = white_list_bump;
+        self.meta_list_bump = meta_list_bump;
+        self.allow_burn = true;
+        self.fee_denominator = Self::FEE_DENOMINATOR;
+    }
+
+    /*
+     * Xác định phiên bản layout của dữ liệu account whitelist dựa vào vị trí của authority
+     *
+     * Phiên bản 0 (trước khi có trường version) lưu authority ngay sau discriminator,
+     * các phiên bản sau lưu version rồi mới tới authority
+     * Trả về Unauthorized nếu authority không khớp ở cả hai vị trí
+     */
+    pub fn stored_version(data: &[u8], authority: &Pubkey) -> Result<u8> {
+        if data.len() < Self::WHITE_LIST_LEN_OFFSET + 4 || &data[..8] != Self::DISCRIMINATOR {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if data[9..41] == authority.as_ref()[..] {
+            return Ok(data[8]);
+        }
+        if data[8..40] == authority.as_ref()[..] {
+            return Ok(0);
+        }
+        err!(TransferError::Unauthorized)
+    }
+
+    // Chuyển dữ liệu phiên bản 0 sang layout hiện tại: dời phần sau discriminator thêm 1 byte
+    // và ghi version, data phải dài hơn old_len ít nhất 1 byte
+    pub fn insert_version(data: &mut [u8], old_len: usize) {
+        data.copy_within(8..old_len, 9);
+        data[8] = Self::VERSION;
+    }
+
+    /*
+     * Chuyển dữ liệu phiên bản version (0 hoặc 1) sang layout hiện tại
+     *
+     * old_len là kích thước dữ liệu trước khi tăng, data phải đủ chỗ cho space_for(số địa chỉ)
+     * Các trường phí (phiên bản 2) nằm sau same_owner_only, ở account cũ vị trí này có thể
+     * còn byte cũ sau khi vector bị thu ngắn nên được đặt lại về giá trị mặc định (tắt phí)
+     */
+    pub fn upgrade_layout(data: &mut [u8], version: u8, old_len: usize) -> Result<Self> {
+        match version {
+            0 => Self::insert_version(data, old_len),
+            1 => data[8] = Self::VERSION,
+            _ => return err!(ErrorCode::AccountDidNotDeserialize),
+        }
+        let mut white_list = Self::try_deserialize(&mut &data[..])?;
+        white_list.fee_numerator = 0;
+        white_list.fee_denominator = Self::FEE_DENOMINATOR;
+        Ok(white_list)
+    }
+
+    // Mẫu số phí đang dùng, 0 được hiểu là FEE_DENOMINATOR
+    pub fn fee_denominator(&self) -> u32 {
+        if self.fee_denominator == 0 { Self::FEE_DENOMINATOR } else { self.fee_denominator }
+    }
+
+    /*
+     * Kiểm tra tỉ lệ phí trước khi lưu
+     *
+     * Trả về InvalidFeeRate nếu mẫu số bằng 0 hoặc tử số lớn hơn mẫu số,
+     * FeeTooHigh nếu tỉ lệ vượt quá MAX_FEE_BASIS_POINTS / FEE_DENOMINATOR
+     */
+    pub fn validate_fee(fee_numerator: u32, fee_denominator: u32) -> Result<()> {
+        if fee_denominator == 0 || fee_numerator > fee_denominator {
+            return err!(TransferError::InvalidFeeRate);
+        }
+        // So sánh bằng phép nhân chéo để không mất độ chính xác
+        if (fee_numerator as u64) * (Self::FEE_DENOMINATOR as u64)
+            > (fee_denominator as u64) * (Self::MAX_FEE_BASIS_POINTS as u64)
+        {
+            msg!("Fee of {0}/{1} exceeds the maximum rate", fee_numerator, fee_denominator);
+            return err!(TransferError::FeeTooHigh);
+        }
+        Ok(())
+    }
+
+    // Phí của một giao dịch: amount * fee_numerator / fee_denominator (làm tròn xuống)
+    // Trả về AmountOverflow nếu phép tính bị tràn
+    pub fn fee_for(&self, amount: u64) -> Result<u64> {
+        (amount as u128)
+            .checked_mul(self.fee_numerator as u128)
+            .and_then(|fee| fee.checked_div(self.fee_denominator() as u128))
+            .and_then(|fee| u64::try_from(fee).ok())
+            .ok_or(error!(TransferError::AmountOverflow))
+    }
+
+    // Số lượng tối đa mỗi giao dịch của một cấp, 0 = không giới hạn
+    pub fn tier_limit(&self, tier: u8) -> u64 {
+        match tier {
+            0 => self.tier0_max,
+            _ => self.tier1_max,
+        }
+    }
+
+    // Địa chỉ được phép ghi lại ExtraAccountMetaList
+    pub fn meta_list_manager(&self) -> Pubkey {
+        self.meta_list_authority.unwrap_or(self.authority)
+    }
+
+    // Bump của PDA extra_account_meta_list của mint
+    pub fn find_meta_list_bump(mint: &Pubkey) -> u8 {
+        Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], &crate::ID).1
+    }
+
+    // Kiểm tra một địa chỉ có quyền thêm/xóa địa chỉ trong whitelist không
+    // (authority hoặc một trong các admin)
+    pub fn is_manager(&self, key: &Pubkey) -> bool {
+        self.authority == *key || self.admins.contains(key)
+    }
+
+    // authority là PDA của một program quản trị (set_authority_pda), chỉ ký được
+    // khi program đó thực hiện CPI nên không dùng trực tiếp để quản lý whitelist được
+    pub fn authority_is_pda(&self) -> bool {
+        self.authority_pda.is_some()
+    }
+
+    // Chuyển quyền cho PDA đã được suy ra và kiểm chứng (set_authority_pda)
+    pub fn assign_authority_pda(&mut self, address: Pubkey, authority_pda: AuthorityPda) {
+        self.authority = address;
+        self.pending_authority = None;
+        self.authority_pda = Some(authority_pda);
+    }
+
+    /*
+     * Xóa một admin theo yêu cầu của signer
+     *
+     * Chỉ authority mới được xóa admin (Unauthorized nếu không phải)
+     * authority không bao giờ bị xóa qua danh sách admin, và admin cuối cùng không
+     * được xóa khi authority là PDA (authority_is_pda), trả về CannotRemoveLastAdmin
+     * trong cả hai trường hợp để whitelist không bị khóa vĩnh viễn
+     */
+    pub fn remove_admin(&mut self, signer: &Pubkey, admin: &Pubkey) -> Result<()> {
+        if *signer != self.authority {
+            return err!(TransferError::Unauthorized);
+        }
+        if *admin == self.authority {
+            msg!("Authority cannot be removed via remove_admin");
+            return err!(TransferError::CannotRemoveLastAdmin);
+        }
+        match self.admins.iter().position(|key| key == admin) {
+            Some(_) if self.admins.len() == 1 && self.authority_is_pda() => {
+                msg!("Cannot remove the last admin while the authority is a program PDA");
+                err!(TransferError::CannotRemoveLastAdmin)
+            }
+            Some(index) => {
+                self.admins.remove(index);
+                Ok(())
+            }
+            None => err!(TransferError::AdminNotFound),
+        }
+    }
+
+    // Kiểm tra whitelist không chứa địa chỉ trùng lặp
+    pub fn has_duplicates(&self) -> bool {
+        self.white_list
+            .iter()
+            .enumerate()
+            .any(|(i, entry)| self.white_list[i + 1..].iter().any(|other| other.account == entry.account))
+    }
+
+    // Cập nhật bộ đếm thống kê sau một giao dịch chuyển token thành công
+    pub fn record_transfer(&mut self, amount: u64) -> Result<()> {
+        self.transfer_count = self.transfer_count
+            .checked_add(1)
+            .ok_or(TransferError::CounterOverflow)?;
+        self.total_volume = self.total_volume
+            .checked_add(amount as u128)
+            .ok_or(TransferError::CounterOverflow)?;
+        Ok(())
+    }
+
+    /*
+     * Kiểm tra whitelist đã được khởi tạo đúng cách
+     *
+     * owner là program sở hữu account whitelist, phải là program này
+     * authority mặc định nghĩa là account toàn 0 (chưa được khởi tạo)
+     */
+    pub fn check_initialized(&self, owner: &Pubkey) -> Result<()> {
+        if *owner != crate::ID || self.authority == Pubkey::default() {
+            msg!("White list is not initialized, owner {0}", owner.to_string());
+            return err!(TransferError::UninitializedWhitelist);
+        }
+        Ok(())
+    }
+
+    // Slot của lần thay đổi gần nhất (thêm hoặc xóa) của một địa chỉ
+    pub fn last_change_slot(&self, account: &Pubkey) -> Option<u64> {
+        self.entry(account)
+            .map(|entry| entry.changed_slot)
+            .or_else(|| {
+                self.recent_removals
+                    .iter()
+                    .find(|removal| removal.account == *account)
+                    .map(|removal| removal.slot)
+            })
+    }
+
+    // Chặn thay đổi một địa chỉ trong vòng cooldown_slots kể từ lần thay đổi gần nhất
+    pub fn check_cooldown(&self, account: &Pubkey, slot: u64) -> Result<()> {
+        if self.cooldown_slots == 0 {
+            return Ok(());
+        }
+        if let Some(last_change_slot) = self.last_change_slot(account) {
+            let ready_at = last_change_slot.saturating_add(self.cooldown_slots);
+            if slot < ready_at {
+                msg!("Account {0} is in cooldown for {1} more slot(s)", account.to_string(), ready_at - slot);
+                return err!(TransferError::AddressCooldown);
+            }
+        }
+        Ok(())
+    }
+
+    // Ghi nhận một địa chỉ vừa bị xóa tại slot để áp dụng cooldown_slots khi thêm lại
+    // Các bản ghi đã hết thời gian chờ được dọn trước, khi vẫn đầy thì bỏ bản ghi cũ nhất
+    pub fn note_removal(&mut self, account: Pubkey, slot: u64) {
+        if self.cooldown_slots == 0 {
+            return;
+        }
+        let cooldown_slots = self.cooldown_slots;
+        self.recent_removals.retain(|removal| {
+            removal.account != account && removal.slot.saturating_add(cooldown_slots) > slot
+        });
+        if self.recent_removals.len() >= Self::MAX_RECENT_REMOVALS {
+            self.recent_removals.remove(0);
+        }
+        self.recent_removals.push(RecentRemoval { account, slot });
+    }
+
+    // Chặn giao dịch tự chuyển (account token nguồn trùng account đích) nếu được cấu hình
+    pub fn check_self_transfer(&self, source_token: &Pubkey, destination_token: &Pubkey) -> Result<()> {
+        if self.block_self_transfer && source_token == destination_token {
+            msg!("Self-transfer rejected: {0}", source_token.to_string());
+            return err!(TransferError::SelfTransferNotAllowed);
+        }
+        Ok(())
+    }
+
+    /*
+     * Kiểm tra giao dịch ở chế độ same_owner_only
+     *
+     * Phía gửi và phía nhận phải thuộc cùng một ví, công tắc khẩn cấp vẫn được áp dụng
+     * Trả về TransfersPaused hoặc CrossOwnerTransferBlocked nếu không thỏa mãn
+     * Chỉ thay thế kiểm tra thành viên: check_policy và các giới hạn vẫn chạy sau đó
+     */
+    pub fn check_same_owner(&self, source_owner: &Pubkey, destination_owner: &Pubkey) -> Result<()> {
+        if self.paused {
+            return err!(TransferError::TransfersPaused);
+        }
+        if source_owner != destination_owner {
+            msg!("Cross-owner transfer from {0} to {1}", source_owner.to_string(), destination_owner.to_string());
+            return err!(TransferError::CrossOwnerTransferBlocked);
+        }
+        Ok(())
+    }
+
+    /*
+     * Chặn chuyển vào account token vừa được tạo trong giao dịch nếu được cấu hình
+     *
+     * balance là số dư của account đích sau khi chuyển (hook chạy sau khi số dư được cập nhật),
+     * số dư trước đó bằng 0 được xem là account mới tạo
+     * Account token đích có trong whitelist (đã được kiểm duyệt) luôn được chấp nhận
+     */
+    pub fn check_destination_exists(&self, destination_token: &Pubkey, balance: u64, amount: u64) -> Result<()> {
+        if self.require_existing_destination
+            && balance.saturating_sub(amount) == 0
+            && !self.contains(destination_token)
+        {
+            msg!("Destination token account has no prior balance: {0}", destination_token.to_string());
+            return err!(TransferError::DestinationMustPreExist);
+        }
+        Ok(())
+    }
+
+    // Giao dịch 0 token được miễn kiểm tra whitelist nếu allow_zero_amount được bật
+    // Công tắc khẩn cấp vẫn được áp dụng: khi tạm dừng, giao dịch đi qua kiểm tra bình thường
+    pub fn is_zero_amount_exempt(&self, amount: u64) -> bool {
+        self.allow_zero_amount && amount == 0 && !self.paused
+    }
+
+    // Giao dịch đốt token (chủ sở hữu account đích là incinerator) được miễn kiểm tra whitelist
+    // nếu allow_burn được bật. Công tắc khẩn cấp vẫn được áp dụng như is_zero_amount_exempt
+    pub fn is_burn_exempt(&self, destination_owner: &Pubkey) -> bool {
+        self.allow_burn && destination_owner == &incinerator::ID && !self.paused
+    }
+
+    // Địa chỉ phía nhận được so khớp với whitelist theo match_mode
+    pub fn destination_key(&self, token_account: &Pubkey, owner: &Pubkey) -> Pubkey {
+        match self.match_mode {
+            MatchMode::Owner => *owner,
+            MatchMode::TokenAccount => *token_account,
+        }
+    }
+
+    // Kiểm tra phía gửi có phải là bypass_sender không
+    pub fn is_bypass_sender(&self, sources: &[Pubkey]) -> bool {
+        self.bypass_sender.is_some_and(|sender| sources.contains(&sender))
+    }
+
+    /*
+     * Kiểm tra các chính sách chuyển token được cấu hình trên whitelist
+     *
+     * Dùng chung cho transfer_hook và can_transfer, không phụ thuộc vào account token
+     * sources: Các địa chỉ đại diện cho phía gửi, chỉ cần một địa chỉ có trong whitelist
+     * destination: Chủ sở hữu (ví) của account đích
+     * destination_program: Program sở hữu địa chỉ phía nhận (nếu đã được đăng ký bằng
+     * register_program_vault), được phép nhận nếu có trong allowed_owner_programs
+     * Trả về lỗi của chính sách đầu tiên bị vi phạm
+     */
+    pub fn check_policy(
+        &self,
+        sources: &[Pubkey],
+        destination: &Pubkey,
+        destination_program: Option<Pubkey>,
+        amount: u64,
+        now: i64
+    ) -> Result<()> {
+        // Công tắc khẩn cấp: chặn mọi giao dịch khi đang tạm dừng
+        if self.paused {
+            return err!(TransferError::TransfersPaused);
+        }
+
+        match (self.mode, self.entry(destination)) {
+            // bypass_sender được gửi đến bất kỳ địa chỉ nào, bỏ qua kiểm tra phía nhận
+            // Lưu ý: transfer_hook vẫn gọi check_is_transferring trước nên không thể
+            // lợi dụng bằng cách gọi trực tiếp
+            _ if self.is_bypass_sender(sources) => {
+                msg!("Bypass sender, destination check skipped");
+            }
+            // Vault thuộc sở hữu của một program được phép không cần có trong whitelist
+            (WhitelistMode::AllowList, None)
+                if destination_program.is_some_and(|program| self.allowed_owner_programs.contains(&program)) => {}
+            // Ở chế độ same_owner_only, toàn bộ kiểm tra thành viên được bỏ qua: account đích
+            // không cần có trong whitelist, phần tử bị tạm ngưng, hết hạn hay chưa kích hoạt
+            // cũng không bị chặn (phía gửi là cùng một ví và không được kiểm tra)
+            (WhitelistMode::AllowList, None) if self.same_owner_only => {}
+            // Kiểm tra xem chủ sở hữu (ví) của account đích có trong whitelist không
+            // Nếu không có thì dừng giao dịch (transaction sẽ thất bại)
+            (WhitelistMode::AllowList, None) => {
+                msg!("Account not in white list: {0}", destination.to_string());
+                return err!(TransferError::NotWhitelisted);
+            }
+            // Địa chỉ mới được thêm phải chờ hết activation_delay mới nhận được token
+            // Quyền nhận token đã hết hạn được coi như không có trong whitelist
+            // Địa chỉ bị tạm ngưng được coi như không có trong whitelist
+            (WhitelistMode::AllowList, Some(entry)) if entry.suspended && !self.same_owner_only => {
+                msg!("White list entry suspended: {0}", destination.to_string());
+                return err!(TransferError::AddressSuspended);
+            }
+            (WhitelistMode::AllowList, Some(entry)) if entry.is_expired(now) && !self.same_owner_only => {
+                msg!("White list entry expired: {0}", destination.to_string());
+                return err!(TransferError::EntryExpired);
+            }
+            (WhitelistMode::AllowList, Some(entry)) => {
+                let active_at = entry.added_at.saturating_add(self.activation_delay);
+                if now < active_at && !self.same_owner_only {
+                    msg!("Account becomes active in {0} seconds", active_at - now);
+                    return err!(TransferError::AddressNotYetActive);
+                }
+                // Giới hạn số lượng theo cấp của account đích (nếu được cấu hình)
+                let tier_limit = self.tier_limit(entry.tier);
+                if tier_limit > 0 && amount > tier_limit {
+                    msg!("Transfer amount {0} exceeds the tier {1} limit {2}", amount, entry.tier, tier_limit);
+                    return err!(TransferError::TierLimitExceeded);
+                }
+            }
+            // Ở chế độ DenyList, dừng giao dịch nếu account đích nằm trong danh sách
+            // (phần tử đã hết hạn không còn bị chặn)
+            (WhitelistMode::DenyList, Some(entry)) if !entry.is_expired(now) => {
+                msg!("Account in deny list: {0}", destination.to_string());
+                return err!(TransferError::DestinationBlacklisted);
+            }
+            (WhitelistMode::DenyList, _) => {}
+            // Ở chế độ MerkleRoot, bằng chứng của account đích được kiểm tra riêng
+            // bằng check_merkle_membership vì cần account MerkleMembership
+            (WhitelistMode::MerkleRoot, _) => {}
+            // Ở chế độ Attestation, attestation của account đích được kiểm tra riêng
+            // bằng check_attestation vì cần account attestation
+            (WhitelistMode::Attestation, _) => {}
+        }
+
+        // Kiểm tra phía gửi nếu được bật, chỉ áp dụng cho chế độ AllowList
+        // (không áp dụng ở chế độ same_owner_only vì phía gửi và phía nhận là cùng một ví)
+        if self.check_source &&
+            self.mode == WhitelistMode::AllowList &&
+            !self.same_owner_only &&
+            !sources.iter().any(|source| self.entry(source).is_some_and(|entry| !entry.suspended))
+        {
+            msg!("Source not in white list: {0:?}", sources);
+            return err!(TransferError::SourceNotWhitelisted);
+        }
+
+        // Kiểm tra giới hạn số lượng mỗi giao dịch (nếu được cấu hình)
+        if let Some(max_transfer_amount) = self.max_transfer_amount {
+            if amount > max_transfer_amount {
+                msg!("Transfer amount {0} exceeds the maximum {1}", amount, max_transfer_amount);
+                return err!(TransferError::TransferAmountExceeded);
+            }
+        }
+
+        // Kiểm tra số lượng tối thiểu mỗi giao dịch (nếu được cấu hình)
+        if self.min_transfer_amount > 0 && amount < self.min_transfer_amount {
+            msg!("Transfer amount {0} is below the minimum {1}", amount, self.min_transfer_amount);
+            return err!(TransferError::TransferAmountTooSmall);
+        }
+
+        Ok(())
+    }
+
+    // Ảnh chụp cấu hình hiện tại, data_len là kích thước thực tế của account
+    pub fn config(&self, data_len: usize) -> WhitelistConfig {
+        WhitelistConfig {
+            version: self.version,
+            authority: self.authority,
+            pending_authority: self.pending_authority,
+            program_authority: self.program_authority,
+            authority_pda: self.authority_pda.clone(),
+            meta_list_authority: self.meta_list_authority,
+            admins: self.admins.clone(),
+            paused: self.paused,
+            mode: self.mode,
+            match_mode: self.match_mode,
+            check_source: self.check_source,
+            block_self_transfer: self.block_self_transfer,
+            require_existing_destination: self.require_existing_destination,
+            record_removals: self.record_removals,
+            prevent_empty: self.prevent_empty,
+            require_confirmation: self.require_confirmation,
+            allow_zero_amount: self.allow_zero_amount,
+            allow_burn: self.allow_burn,
+            same_owner_only: self.same_owner_only,
+            verbose_logging: self.verbose_logging,
+            pending_additions: self.pending_additions.len() as u32,
+            length: self.white_list.len() as u32,
+            capacity: Self::capacity_for(data_len).min(u32::MAX as usize) as u32,
+            max_entries: self.max_entries,
+            max_transfer_amount: self.max_transfer_amount,
+            min_transfer_amount: self.min_transfer_amount,
+            tier0_max: self.tier0_max,
+            tier1_max: self.tier1_max,
+            activation_delay: self.activation_delay,
+            cooldown_slots: self.cooldown_slots,
+            max_per_window: self.max_per_window,
+            window_seconds: self.window_seconds,
+            per_epoch_quota: self.per_epoch_quota,
+            max_destinations_per_window: self.max_destinations_per_window,
+            fan_out_window_seconds: self.fan_out_window_seconds,
+            required_mint: self.required_mint,
+            bypass_sender: self.bypass_sender,
+            allowed_owner_programs: self.allowed_owner_programs.clone(),
+            merkle_root: self.merkle_root,
+            attestation_program: self.attestation_program,
+            attestation_expiry_offset: self.attestation_expiry_offset,
+            fee_numerator: self.fee_numerator,
+            fee_denominator: self.fee_denominator(),
+        }
+    }
+
+    /*
+     * Kiểm tra toàn bộ các bất biến của cấu hình
+     *
+     * data_len là kích thước thực tế của account, dùng để kiểm tra
+     * dữ liệu vẫn nằm gọn trong không gian đã cấp phát
+     */
+    pub fn config_violations(&self, data_len: usize) -> Vec<ConfigViolation> {
+        let mut violations = Vec::new();
+        if self.authority == Pubkey::default() {
+            violations.push(ConfigViolation::UninitializedAuthority);
+        }
+        if self.has_duplicates() {
+            violations.push(ConfigViolation::DuplicateEntry);
+        }
+        if self.serialized_len() > data_len {
+            violations.push(ConfigViolation::ExceedsAllocatedSpace);
+        }
+        if self.max_transfer_amount.is_some_and(|max| self.min_transfer_amount > max) {
+            violations.push(ConfigViolation::MinAboveMax);
+        }
+        if !self.is_sorted() {
+            violations.push(ConfigViolation::UnsortedEntries);
+        }
+        // Dùng cùng kiểm tra với set_fee_rate, mẫu số 0 được hiểu là FEE_DENOMINATOR
+        if Self::validate_fee(self.fee_numerator, self.fee_denominator()).is_err() {
+            violations.push(ConfigViolation::InvalidFeeRate);
+        }
+        violations
+    }
+}
+
+/*
+ * Layout của whitelist toàn cục cũ (PDA từ seeds "white_list", dùng chung cho mọi mint)
+ *
+ * Chỉ dùng để đọc dữ liệu khi di chuyển sang whitelist theo từng mint,
+ * account cũ có cùng discriminator với WhiteList
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyWhiteList {
+    // Địa chỉ có quyền thêm/xóa địa chỉ trong whitelist
+    pub authority: Pubkey,
+    // Danh sách các địa chỉ được phép nhận token
+    pub white_list: Vec<Pubkey>,
+}
+
+impl LegacyWhiteList {
+    // Đọc whitelist cũ từ dữ liệu account (bỏ qua phần dữ liệu thừa phía sau)
+    pub fn try_from_account(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        if data.len() < 8 || &data[..8] != WhiteList::DISCRIMINATOR {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+/*
+ * Cấu hình token thành viên (membership) cho mỗi mint
+ *
+ * Khi required_balance > 0, chủ sở hữu account đích phải nắm giữ ít nhất
+ * required_balance token của required_mint thì mới nhận được token
+ * Lưu ý: Layout của account này có kích thước cố định vì các trường
+ * required_mint và required_mint_token_program được dùng làm seed
+ * (Seed::AccountData) để suy ra account token thành viên trong ExtraAccountMetaList
+ * Cùng layout này cũng được dùng cho PDA "required_mint" để suy ra account token
+ * của WhiteList::required_mint (khi đó required_balance không được sử dụng)
+ */
+#[account]
+pub struct MembershipConfig {
+    // Mint của token thành viên
+    pub required_mint: Pubkey,
+    // Token program sở hữu required_mint (Token hoặc Token-2022), dùng trong seed của ATA
+    pub required_mint_token_program: Pubkey,
+    // Số dư tối thiểu cần nắm giữ, 0 = tắt tính năng
+    pub required_balance: u64,
+}
+
+impl MembershipConfig {
+    // Kích thước account: discriminator (8) + 2 Pubkey (64) + u64 (8)
+    pub const LEN: usize = 8 + 32 + 32 + 8;
+    // Vị trí của các trường trong dữ liệu account (tính cả discriminator)
+    pub const REQUIRED_MINT_OFFSET: u8 = 8;
+    pub const REQUIRED_MINT_TOKEN_PROGRAM_OFFSET: u8 = 40;
+}
+
+/*
+ * Lịch giải phóng (vesting) cho một account escrow
+ *
+ * Token chuyển vào escrow được cộng dồn vào total_deposited
+ * Token chuyển ra khỏi escrow bị giới hạn bởi số token đã được giải phóng
+ * theo lịch tuyến tính: không có gì trước cliff, giải phóng hết sau duration
+ * Lưu ý: Lịch chỉ giới hạn chiều chuyển RA khỏi escrow, account đích
+ * vẫn phải thỏa mãn các kiểm tra whitelist như bình thường
+ */
+#[account]
+pub struct ReleaseSchedule {
+    // account token escrow được áp dụng lịch
+    pub escrow: Pubkey,
+    // Thời điểm bắt đầu tính lịch (unix timestamp)
+    pub start_ts: i64,
+    // Số giây kể từ start_ts trước khi bắt đầu giải phóng
+    pub cliff: i64,
+    // Số giây kể từ start_ts để giải phóng toàn bộ
+    pub duration: i64,
+    // Tổng số token đã được chuyển vào escrow
+    pub total_deposited: u64,
+    // Tổng số token đã được chuyển ra khỏi escrow
+    pub withdrawn: u64,
+}
+
+impl ReleaseSchedule {
+    // Kích thước account: discriminator (8) + Pubkey (32) + 3 i64 (24) + 2 u64 (16)
+    pub const LEN: usize = 8 + 32 + 8 * 3 + 8 * 2;
+
+    // Số token đã được giải phóng tại thời điểm now
+    pub fn released_at(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_ts);
+        if elapsed < self.cliff {
+            return 0;
+        }
+        if elapsed >= self.duration {
+            return self.total_deposited;
+        }
+        ((self.total_deposited as u128) * (elapsed as u128) / (self.duration as u128)) as u64
+    }
+
+    // Ghi nhận amount được chuyển ra khỏi escrow tại thời điểm now
+    // Trả về AmountNotYetReleased nếu vượt quá số token đã giải phóng còn lại
+    pub fn withdraw(&mut self, amount: u64, now: i64) -> Result<()> {
+        let available = self.released_at(now).saturating_sub(self.withdrawn);
+        if amount > available {
+            msg!("Escrow has {0} released tokens available, attempted {1}", available, amount);
+            return err!(TransferError::AmountNotYetReleased);
+        }
+        self.withdrawn += amount;
+        Ok(())
+    }
+}
+
+/*
+ * Giới hạn tổng số token một account đích được nhận
+ *
+ * Được authority tạo bằng set_receiving_cap, transfer_hook cộng dồn
+ * số token nhận được vào received sau mỗi giao dịch
+ * account đích chưa có giới hạn thì không bị giới hạn
+ */
+#[account]
+pub struct ReceivingLimit {
+    // account token đích được áp dụng giới hạn
+    pub destination: Pubkey,
+    // Tổng số token tối đa được nhận
+    pub cap: u128,
+    // Tổng số token đã nhận kể từ khi giới hạn được tạo
+    pub received: u128,
+}
+
+impl ReceivingLimit {
+    // Kích thước account: discriminator (8) + Pubkey (32) + 2 u128 (32)
+    pub const LEN: usize = 8 + 32 + 16 * 2;
+}
+
+/*
+ * Kết quả xác minh bằng chứng Merkle của một địa chỉ (chế độ MerkleRoot)
+ *
+ * Token-2022 không cho phép truyền thêm dữ liệu vào transfer hook, vì vậy
+ * bằng chứng được gửi trước bằng submit_merkle_proof và kết quả được lưu ở đây
+ * Khi authority đổi merkle_root, các kết quả cũ không còn hiệu lực
+ */
+#[account]
+pub struct MerkleMembership {
+    // Địa chỉ ví đã được xác minh
+    pub owner: Pubkey,
+    // Root tại thời điểm xác minh
+    pub root: [u8; 32],
+}
+
+impl MerkleMembership {
+    // Kích thước account: discriminator (8) + Pubkey (32) + root (32)
+    pub const LEN: usize = 8 + 32 + 32;
+}
+
+/*
+ * Bộ đếm số giao dịch của một owner nguồn trong khoảng thời gian hiện tại
+ *
+ * Khi giới hạn được bật, owner phải tạo account này (create_rate_counter)
+ * trước khi chuyển token vì transfer hook không thể trả phí tạo account
+ */
+#[account]
+pub struct RateCounter {
+    // Địa chỉ ví (owner) nguồn
+    pub owner: Pubkey,
+    // Thời điểm bắt đầu khoảng thời gian hiện tại (unix timestamp)
+    pub window_start: i64,
+    // Số giao dịch đã thực hiện trong khoảng thời gian hiện tại
+    pub count: u32,
+}
+
+impl RateCounter {
+    // Kích thước account: discriminator (8) + Pubkey (32) + i64 (8) + u32 (4)
+    pub const LEN: usize = 8 + 32 + 8 + 4;
+
+    // Ghi nhận một giao dịch tại thời điểm now, bắt đầu khoảng thời gian mới nếu đã hết hạn
+    // Trả về false nếu vượt quá max_per_window
+    pub fn record(&mut self, now: i64, max_per_window: u32, window_seconds: i64) -> bool {
+        if now >= self.window_start.saturating_add(window_seconds) {
+            self.window_start = now;
+            self.count = 0;
+        }
+        self.count = self.count.saturating_add(1);
+        self.count <= max_per_window
+    }
+}
+
+/*
+ * Tổng số lượng một owner nguồn đã chuyển trong epoch hiện tại
+ *
+ * Khi per_epoch_quota được bật, owner phải tạo account này (create_epoch_quota)
+ * trước khi chuyển token vì transfer hook không thể trả phí tạo account
+ */
+#[account]
+pub struct EpochQuota {
+    // Địa chỉ ví (owner) nguồn
+    pub owner: Pubkey,
+    // Epoch của lần chuyển gần nhất
+    pub epoch: u64,
+    // Tổng số lượng đã chuyển trong epoch
+    pub moved: u128,
+}
+
+impl EpochQuota {
+    // Kích thước account: discriminator (8) + Pubkey (32) + u64 (8) + u128 (16)
+    pub const LEN: usize = 8 + 32 + 8 + 16;
+
+    // Ghi nhận amount được chuyển trong epoch, đặt lại tổng khi sang epoch mới
+    // Trả về false nếu tổng vượt quá quota
+    pub fn record(&mut self, epoch: u64, amount: u64, quota: u64) -> bool {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.moved = 0;
+        }
+        self.moved = self.moved.saturating_add(amount as u128);
+        self.moved <= quota as u128
+    }
+}
+
+/*
+ * Treasury của mỗi mint, ghi nhận tổng phí giao thức phải thu
+ *
+ * transfer_hook chỉ ghi nhận nghĩa vụ phí, việc chuyển token phí được thực hiện riêng
+ * Được tạo bởi set_fee_basis_points hoặc set_fee_rate vì transfer hook không thể trả phí tạo account
+ */
+#[account]
+pub struct Treasury {
+    // account mint của token
+    pub mint: Pubkey,
+    // Tổng phí đã ghi nhận
+    pub accrued_fees: u64,
+}
+
+impl Treasury {
+    // Kích thước account: discriminator (8) + Pubkey (32) + u64 (8)
+    pub const LEN: usize = 8 + 32 + 8;
+
+    // Cộng thêm phí của một giao dịch, trả về AmountOverflow nếu tổng bị tràn
+    pub fn accrue(&mut self, fee: u64) -> Result<()> {
+        self.accrued_fees = self.accrued_fees.checked_add(fee).ok_or(TransferError::AmountOverflow)?;
+        Ok(())
+    }
+}
+
+/*
+ * Bộ đếm số địa chỉ nhận mới của một owner nguồn trong khoảng thời gian hiện tại
+ *
+ * Theo dõi chính xác tập địa chỉ nhận là quá tốn kém, nên chỉ MAX_TRACKED địa chỉ
+ * gần nhất được lưu: gửi đến một địa chỉ trong số đó không được tính là địa chỉ mới
+ * Owner phải tạo account này (create_fan_out_counter) trước khi chuyển token
+ */
+#[account]
+pub struct FanOutCounter {
+    // Địa chỉ ví (owner) nguồn
+    pub owner: Pubkey,
+    // Thời điểm bắt đầu khoảng thời gian hiện tại (unix timestamp)
+    pub window_start: i64,
+    // Số địa chỉ nhận mới trong khoảng thời gian hiện tại
+    pub count: u32,
+    // Các địa chỉ nhận gần nhất (tối đa MAX_TRACKED), phần tử cũ nhất bị thay thế khi đầy
+    pub recent_destinations: Vec<Pubkey>,
+}
+
+impl FanOutCounter {
+    // Số địa chỉ nhận gần nhất được lưu
+    pub const MAX_TRACKED: usize = 8;
+    // Kích thước account: discriminator (8) + Pubkey (32) + i64 (8) + u32 (4)
+    // + recent_destinations (4 + MAX_TRACKED * 32)
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 4 + Self::MAX_TRACKED * 32;
+
+    // Ghi nhận một giao dịch đến destination tại thời điểm now, bắt đầu khoảng thời gian mới
+    // nếu đã hết hạn. Trả về false nếu số địa chỉ nhận mới vượt quá max_destinations
+    pub fn record(&mut self, destination: Pubkey, now: i64, max_destinations: u32, window_seconds: i64) -> bool {
+        if now >= self.window_start.saturating_add(window_seconds) {
+            self.window_start = now;
+            self.count = 0;
+            self.recent_destinations.clear();
+        }
+        if self.recent_destinations.contains(&destination) {
+            return true;
+        }
+        if self.recent_destinations.len() >= Self::MAX_TRACKED {
+            self.recent_destinations.remove(0);
+        }
+        self.recent_destinations.push(destination);
+        self.count = self.count.saturating_add(1);
+        self.count <= max_destinations
+    }
+}
+
+/*
+ * Đăng ký một địa chỉ (vault PDA) thuộc sở hữu của một program được phép
+ *
+ * transfer_hook không thể đọc account của chủ sở hữu account đích (không suy ra
+ * được bằng ExtraAccountMeta), nên program sở hữu được kiểm tra và lưu lại
+ * trước bằng register_program_vault
+ */
+#[account]
+pub struct ProgramVault {
+    // Địa chỉ vault (chủ sở hữu của account token)
+    pub owner: Pubkey,
+    // Program sở hữu vault tại thời điểm đăng ký
+    pub program: Pubkey,
+}
+
+impl ProgramVault {
+    // Kích thước account: discriminator (8) + 2 Pubkey (64)
+    pub const LEN: usize = 8 + 32 + 32;
+}
+
+/*
+ * Nhật ký các địa chỉ đã bị xóa khỏi whitelist của một mint
+ *
+ * Được tạo bằng create_removal_audit với dung lượng cố định, chỉ được ghi thêm
+ * khi record_removals được bật. Khi đầy, authority cần xóa bớt các bản ghi cũ
+ * bằng prune_removal_audit
+ */
+#[account]
+pub struct RemovalAudit {
+    // Bump của PDA
+    pub bump: u8,
+    // Các bản ghi theo thứ tự thời gian
+    pub records: Vec<RemovalRecord>,
+}
+
+/*
+ * Một bản ghi của nhật ký xóa
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RemovalRecord {
+    // Địa chỉ bị xóa khỏi whitelist
+    pub account: Pubkey,
+    // Thời điểm bị xóa (unix timestamp)
+    pub removed_at: i64,
+}
+
+impl RemovalAudit {
+    // Kích thước phần cố định: discriminator (8) + bump (1) + tiền tố độ dài vector (4)
+    pub const BASE_LEN: usize = 8 + 1 + 4;
+    // Mỗi bản ghi: Pubkey (32) + removed_at (8)
+    pub const RECORD_LEN: usize = 32 + 8;
+    // Số bản ghi tối đa có thể trả về qua return data
+    pub const MAX_RETURN_DATA_RECORDS: usize = (1024 - 4) / Self::RECORD_LEN;
+
+    // Số bytes cần thiết để lưu nhật ký chứa tối đa capacity bản ghi
+    pub fn space_for(capacity: usize) -> usize {
+        Self::BASE_LEN + capacity * Self::RECORD_LEN
+    }
+
+    // Số bản ghi tối đa có thể lưu trong account có kích thước data_len
+    pub fn capacity_for(data_len: usize) -> usize {
+        data_len.saturating_sub(Self::BASE_LEN) / Self::RECORD_LEN
+    }
+
+    // Ghi các địa chỉ bị xóa tại thời điểm now, trả về AuditLogFull nếu không đủ chỗ cho tất cả
+    pub fn record(&mut self, accounts: &[Pubkey], removed_at: i64, capacity: usize) -> Result<()> {
+        if self.records.len().saturating_add(accounts.len()) > capacity {
+            msg!(
+                "Removal audit log is full: {0}/{1} records, cannot add {2}",
+                self.records.len(),
+                capacity,
+                accounts.len()
+            );
+            return err!(TransferError::AuditLogFull);
+        }
+        self.records.extend(accounts.iter().map(|account| RemovalRecord { account: *account, removed_at }));
+        Ok(())
+    }
+
+    // Xóa các bản ghi cũ hơn thời điểm before, trả về số bản ghi đã xóa
+    pub fn prune(&mut self, before: i64) -> usize {
+        let len = self.records.len();
+        self.records.retain(|record| record.removed_at >= before);
+        len - self.records.len()
+    }
+
+    // Một trang của nhật ký, giới hạn trong vector và trong MAX_RETURN_DATA_RECORDS
+    pub fn page(&self, offset: u32, limit: u32) -> Vec<RemovalRecord> {
+        let limit = (limit as usize).min(Self::MAX_RETURN_DATA_RECORDS);
+        self.records.iter().skip(offset as usize).take(limit).copied().collect()
+    }
+}
+
+/*
+ * Ảnh chụp whitelist tại một slot, phục vụ kiểm toán ("ai có trong whitelist vào ngày X?")
+ *
+ * Được tạo bằng snapshot_whitelist (seeds "snapshot", địa chỉ mint và slot dạng little-endian),
+ * không thể sửa đổi sau khi tạo, chỉ authority có thể đóng để thu hồi rent
+ */
+#[account]
+pub struct WhitelistSnapshot {
+    // Bump của PDA
+    pub bump: u8,
+    // Slot dùng trong seeds của PDA
+    pub slot: u64,
+    // Slot và thời điểm (unix timestamp) ảnh chụp thực sự được ghi
+    pub recorded_slot: u64,
+    pub recorded_at: i64,
+    // Các địa chỉ trong whitelist tại thời điểm chụp (theo thứ tự sắp xếp)
+    pub accounts: Vec<Pubkey>,
+}
+
+impl WhitelistSnapshot {
+    // Kích thước phần cố định: discriminator (8) + bump (1) + slot (8) + recorded_slot (8)
+    // + recorded_at (8) + tiền tố độ dài vector (4)
+    pub const BASE_LEN: usize = 8 + 1 + 8 + 8 + 8 + 4;
+    // Số địa chỉ tối đa của một ảnh chụp, giữ account trong giới hạn 10240 bytes
+    // khi được tạo qua CPI của System Program
+    pub const MAX_ACCOUNTS: usize = 300;
+    // Số slot tối đa giữa slot trong seeds và slot thực thi (khoảng thời gian hiệu lực của blockhash)
+    pub const MAX_SLOT_AGE: u64 = 150;
+
+    // Số bytes cần thiết để lưu ảnh chụp chứa len địa chỉ
+    pub fn space_for(len: usize) -> usize {
+        Self::BASE_LEN + len * 32
+    }
+
+    // Slot trong seeds phải không ở tương lai và không cũ hơn MAX_SLOT_AGE
+    pub fn check_slot(slot: u64, current_slot: u64) -> Result<()> {
+        if slot > current_slot || current_slot - slot > Self::MAX_SLOT_AGE {
+            msg!("Snapshot slot {0} is not within {1} slots of {2}", slot, Self::MAX_SLOT_AGE, current_slot);
+            return err!(TransferError::InvalidSnapshotSlot);
+        }
+        Ok(())
+    }
+
+    // Một trang của ảnh chụp, giới hạn trong vector và trong MAX_RETURN_DATA_KEYS
+    pub fn page(&self, offset: u32, limit: u32) -> Vec<Pubkey> {
+        let limit = (limit as usize).min(MAX_RETURN_DATA_KEYS);
+        self.accounts.iter().skip(offset as usize).take(limit).copied().collect()
+    }
+}
+
+/*
+ * Kết quả quyết định của transfer hook
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Decision {
+    // Giao dịch chuyển token được cho phép
+    Allow,
+    // Giao dịch chuyển token bị từ chối
+    Reject,
+}
+
+/*
+ * Sự kiện chuẩn được phát ra trong mọi lần transfer hook được gọi
+ *
+ * Đây là bản ghi chính thức mà các indexer nên theo dõi
+ * (có trong IDL nên client Anchor giải mã được theo kiểu)
+ * Với giao dịch bị từ chối, sự kiện vẫn nằm trong log của giao dịch thất bại
+ */
+#[event]
+pub struct TransferDecision {
+    // account token nguồn
+    pub source: Pubkey,
+    // account token đích
+    pub destination: Pubkey,
+    // Số lượng token chuyển
+    pub amount: u64,
+    // Quyết định: cho phép hoặc từ chối
+    pub decision: Decision,
+    // Mã lỗi khi bị từ chối (ví dụ 6000 + chỉ số trong TransferError), 0 khi được cho phép
+    pub reason_code: u32,
+    // Thời điểm xử lý (unix timestamp)
+    pub timestamp: i64,
+    // Slot xử lý
+    pub slot: u64,
+}
+
+/*
+ * Sự kiện phát ra cho mỗi giao dịch chuyển token thành công khi verbose_logging được bật
+ *
+ * Nguồn dữ liệu kiểm toán gọn nhẹ, không cần account bổ sung
+ */
+#[event]
+pub struct TransferObserved {
+    // account token nguồn
+    pub source: Pubkey,
+    // account token đích
+    pub destination: Pubkey,
+    // Số lượng token chuyển
+    pub amount: u64,
+    // Slot xử lý
+    pub slot: u64,
+}
+
+/*
+ * Sự kiện phát ra khi transfer_hook bị gọi trực tiếp ngoài ngữ cảnh chuyển token
+ *
+ * Có thể là dấu hiệu dò tìm lỗ hổng, dùng cho hệ thống giám sát bảo mật
+ * (sự kiện nằm trong log của giao dịch thất bại)
+ */
+#[event]
+pub struct DirectCallAttempt {
+    // account token nguồn được truyền vào
+    pub source: Pubkey,
+    // owner được truyền vào
+    pub owner: Pubkey,
+}
+
+/*
+ * Sự kiện phát ra khi một địa chỉ được thêm vào whitelist
+ */
+#[event]
+pub struct WhitelistAdded {
+    // Địa chỉ được thêm
+    pub account: Pubkey,
+    // authority thực hiện thay đổi
+    pub authority: Pubkey,
+    // Độ dài whitelist sau khi thêm
+    pub length: u32,
+}
+
+/*
+ * Sự kiện phát ra khi một địa chỉ bị xóa khỏi whitelist
+ */
+#[event]
+pub struct WhitelistRemoved {
+    // Địa chỉ bị xóa
+    pub account: Pubkey,
+    // authority thực hiện thay đổi
+    pub authority: Pubkey,
+    // Độ dài whitelist sau khi xóa
+    pub length: u32,
+}
+
+/*
+ * Sự kiện phát ra khi toàn bộ whitelist bị xóa
+ */
+#[event]
+pub struct WhitelistCleared {
+    // authority thực hiện thay đổi
+    pub authority: Pubkey,
+    // Số địa chỉ đã bị xóa
+    pub cleared: u32,
+}
+
+/*
+ * Sự kiện phát ra khi một địa chỉ được đưa vào danh sách chờ xác nhận
+ */
+#[event]
+pub struct AdditionProposed {
+    // Địa chỉ được đề xuất thêm
+    pub account: Pubkey,
+    // Admin đã đề xuất
+    pub proposer: Pubkey,
+}
+
+/*
+ * Sự kiện phát ra khi toàn bộ whitelist được thay thế bằng một danh sách mới
+ */
+#[event]
+pub struct WhitelistReplaced {
+    // authority thực hiện thay đổi
+    pub authority: Pubkey,
+    // Số địa chỉ trong whitelist mới
+    pub length: u32,
+}
+
+/*
+ * Sự kiện phát ra khi whitelist của một mint được khởi tạo lần đầu
+ */
+#[event]
+pub struct WhitelistInitialized {
+    // Mint sở hữu whitelist
+    pub mint: Pubkey,
+    // authority của whitelist
+    pub authority: Pubkey,
+    // Số địa chỉ tối đa của whitelist
+    pub max_entries: u16,
+}
+
+// PHẦN 4: CÁC CẤU TRÚC account VÀ LOGIC LIÊN QUAN
+/*
+ * Định nghĩa cấu trúc account cho hàm khởi tạo ExtraAccountMetaList
+ *
+ * Mô tả các account cần thiết và cách chúng được xác thực
+ * khi gọi hàm initialize_extra_account_meta_list
+ */
+#[derive(Accounts)]
+#[instruction(max_entries: u16)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    // Người trả phí cho việc tạo account
+    // mut: account này có thể bị trừ lamports
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    // account PDA lưu trữ thông tin về các account bổ sung
+    // Được tạo từ seeds bao gồm "extra-account-metas" và địa chỉ của mint
+    // Đây là account quan trọng cho Transfer Hook, Token-2022 sẽ sử dụng nó
+    // để biết cần truy xuất account bổ sung nào khi chuyển token
+    #[account(
+        init,  // Khởi tạo account mới
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],  // Seeds để tạo PDA
+        bump,  // Bump seed sẽ được tự động tính toán
+        space = InitializeExtraAccountMetaList::extra_account_meta_list_size()?,  // Kích thước của account
+        payer = payer  // Người trả phí cho việc tạo account
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+    // account lưu trữ whitelist
+    // Được tạo từ seeds "white_list" và địa chỉ mint
+    // init_if_needed: Tạo mới nếu chưa tồn tại
+    // space: Đủ chỗ cho max_entries địa chỉ, payer chỉ trả rent cho phần cần dùng
+    #[account(
+        init_if_needed,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WhiteList::space_for(max_entries as usize)
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // account cấu hình token thành viên cho mint này
+    // Luôn được tạo (với required_balance = 0) để ExtraAccountMetaList
+    // có thể đọc dữ liệu của nó khi suy ra account token thành viên
+    #[account(
+        init,
+        seeds = [MEMBERSHIP_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = MembershipConfig::LEN
+    )]
+    pub membership_config: Account<'info, MembershipConfig>,
+    // account lưu mint bắt buộc nắm giữ và token program của nó
+    // Luôn được tạo (toàn 0) vì lý do tương tự membership_config
+    #[account(
+        init,
+        seeds = [REQUIRED_MINT_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = MembershipConfig::LEN
+    )]
+    pub required_mint_config: Account<'info, MembershipConfig>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm khởi tạo ExtraAccountMetaList chặt chẽ
+ *
+ * Giống InitializeExtraAccountMetaList nhưng white_list dùng init thay vì init_if_needed:
+ * giao dịch thất bại nếu whitelist đã tồn tại
+ */
+#[derive(Accounts)]
+#[instruction(max_entries: u16)]
+pub struct InitializeExtraAccountMetaListStrict<'info> {
+    // Người trả phí cho việc tạo account, trở thành authority của whitelist
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    // account PDA lưu trữ thông tin về các account bổ sung
+    #[account(
+        init,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump,
+        space = InitializeExtraAccountMetaList::extra_account_meta_list_size()?,
+        payer = payer
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+    // account lưu trữ whitelist, phải chưa tồn tại
+    #[account(
+        init,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WhiteList::space_for(max_entries as usize)
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // account cấu hình token thành viên cho mint này (xem InitializeExtraAccountMetaList)
+    #[account(
+        init,
+        seeds = [MEMBERSHIP_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = MembershipConfig::LEN
+    )]
+    pub membership_config: Account<'info, MembershipConfig>,
+    // account lưu mint bắt buộc nắm giữ (xem InitializeExtraAccountMetaList)
+    #[account(
+        init,
+        seeds = [REQUIRED_MINT_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = MembershipConfig::LEN
+    )]
+    pub required_mint_config: Account<'info, MembershipConfig>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm chỉ khởi tạo whitelist
+ *
+ * Không tạo ExtraAccountMetaList, cho phép chuẩn bị whitelist trước khi
+ * kích hoạt transfer hook
+ */
+#[derive(Accounts)]
+#[instruction(authority: Pubkey, max_entries: u16)]
+pub struct InitializeWhiteList<'info> {
+    // Người trả phí tạo account, phải là mint authority của mint
+    // để không ai khác có thể chiếm trước whitelist của mint
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // account mint của token
+    #[account(
+        constraint = mint.mint_authority == COption::Some(payer.key()) @ TransferError::Unauthorized
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist của mint
+    #[account(
+        init,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = WhiteList::space_for(max_entries as usize)
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa các account bổ sung cần thiết cho Transfer Hook
+ * 
+ * Mô tả các account bổ sung cần được cung cấp tự động
+ * khi Token-2022 gọi transfer hook
+ */
+impl<'info> InitializeExtraAccountMetaList<'info> {
+    /*
+     * Khởi tạo whitelist và ExtraAccountMetaList
+     *
+     * Dùng chung cho initialize_extra_account_meta_list và initialize_with_accounts
+     */
+    pub fn initialize(&mut self, max_entries: u16, bumps: &InitializeExtraAccountMetaListBumps) -> Result<()> {
+        // white_list dùng init_if_needed nên có thể đã tồn tại (ví dụ sau initialize_whitelist
+        // hoặc migrate_whitelist). Khi đó whitelist được giữ nguyên, không ghi đè authority,
+        // nếu không bất kỳ payer nào cũng có thể chiếm quyền
+        if self.white_list.authority == Pubkey::default() {
+            // Thiết lập quyền sở hữu (authority) của whitelist là người trả phí (payer)
+            // Điều này xác định ai có quyền thêm/xóa địa chỉ trong whitelist
+            self.white_list.initialize(self.payer.key(), max_entries, bumps.white_list, bumps.extra_account_meta_list);
+            emit!(WhitelistInitialized {
+                mint: self.mint.key(),
+                authority: self.white_list.authority,
+                max_entries,
+            });
+        } else {
+            if self.white_list.version != WhiteList::VERSION {
+                return err!(TransferError::MigrationRequired);
+            }
+            msg!("Using existing white list of {0}", self.white_list.authority.to_string());
+        }
+
+        // Lấy danh sách các account bổ sung cần thiết cho transfer hook
+        let extra_account_metas = Self::extra_account_metas(self.white_list.attestation_program)?;
+
+        // Khởi tạo account ExtraAccountMetaList với danh sách các account bổ sung
+        // Token-2022 sẽ sử dụng account này để biết cần truy xuất account bổ sung nào
+        // khi thực hiện chuyển token
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut self.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas
+        )?;
+        Ok(())
+    }
+
+    // Kích thước của account ExtraAccountMetaList, tính từ extra_account_metas()
+    // (số account bổ sung không phụ thuộc vào attestation_program)
+    pub fn extra_account_meta_list_size() -> Result<usize> {
+        Ok(ExtraAccountMetaList::size_of(Self::extra_account_metas(None)?.len())?)
+    }
+
+    /*
+     * Danh sách account bổ sung của transfer hook
+     *
+     * attestation_program: Program cấp attestation đã cấu hình trên whitelist,
+     * None thì dùng System Program làm giá trị giữ chỗ để số account không đổi
+     * Lưu ý: Seeds chỉ có thể dùng dữ liệu mà Token-2022 truyền cho hook. Dữ liệu
+     * instruction Execute do Token-2022 tạo chỉ gồm discriminator và amount, nên không
+     * thể chọn whitelist theo từng giao dịch (ví dụ theo group id). Mỗi mint chỉ có
+     * một whitelist; các nhóm cần danh sách khác nhau nên dùng mint riêng
+     */
+    pub fn extra_account_metas(attestation_program: Option<Pubkey>) -> Result<Vec<ExtraAccountMeta>> {
+        Ok(
+            vec![
+                // index 5: account whitelist của mint
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        // Seed để tạo PDA cho account white_list
+                        Seed::Literal {
+                            bytes: WHITE_LIST_SEED.to_vec(),
+                        },
+                        // index 1: account mint, mỗi mint có một whitelist riêng
+                        Seed::AccountKey { index: 1 },
+                    ],
+                    false, // is_signer: false - không yêu cầu account này là signer
+                    true // is_writable: true - account này cần có quyền ghi
+                )?,
+                // index 6: account cấu hình token thành viên của mint
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: MEMBERSHIP_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 7: Associated Token Program
+                ExtraAccountMeta::new_with_pubkey(&AssociatedToken::id(), false, false)?,
+                // index 8: account token thành viên (ATA) của chủ sở hữu account đích
+                // Seeds của ATA: [ví, token program, mint]
+                ExtraAccountMeta::new_external_pda_with_seeds(
+                    7, // Program sinh ra PDA là Associated Token Program (index 7)
+                    &[
+                        // Chủ sở hữu của account token đích (index 2, bytes 32..64)
+                        Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                        // Token program của mint thành viên, đọc từ membership_config
+                        Seed::AccountData {
+                            account_index: 6,
+                            data_index: MembershipConfig::REQUIRED_MINT_TOKEN_PROGRAM_OFFSET,
+                            length: 32,
+                        },
+                        // Mint thành viên, đọc từ membership_config
+                        Seed::AccountData {
+                            account_index: 6,
+                            data_index: MembershipConfig::REQUIRED_MINT_OFFSET,
+                            length: 32,
+                        },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 9: lịch giải phóng của account nguồn (nếu là escrow)
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: RELEASE_SEED.to_vec(),
+                        },
+                        // index 0: account token nguồn
+                        Seed::AccountKey { index: 0 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 10: lịch giải phóng của account đích (nếu là escrow)
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: RELEASE_SEED.to_vec(),
+                        },
+                        // index 2: account token đích
+                        Seed::AccountKey { index: 2 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 11: giới hạn nhận token của account đích (nếu có)
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: RECEIVING_LIMIT_SEED.to_vec(),
+                        },
+                        // index 2: account token đích
+                        Seed::AccountKey { index: 2 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 12: kết quả xác minh Merkle của chủ sở hữu account đích
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: MERKLE_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                        // Chủ sở hữu của account token đích (index 2, bytes 32..64)
+                        Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 13: bộ đếm giới hạn tần suất của owner nguồn
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: RATE_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                        // index 3: owner của account nguồn
+                        Seed::AccountKey { index: 3 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 14: account lưu mint bắt buộc nắm giữ của mint
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: REQUIRED_MINT_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 15: account token (ATA) của chủ sở hữu account đích cho mint bắt buộc
+                ExtraAccountMeta::new_external_pda_with_seeds(
+                    7, // Associated Token Program (index 7)
+                    &[
+                        // Chủ sở hữu của account token đích (index 2, bytes 32..64)
+                        Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                        // Token program của mint bắt buộc, đọc từ required_mint_config
+                        Seed::AccountData {
+                            account_index: 14,
+                            data_index: MembershipConfig::REQUIRED_MINT_TOKEN_PROGRAM_OFFSET,
+                            length: 32,
+                        },
+                        // Mint bắt buộc, đọc từ required_mint_config
+                        Seed::AccountData {
+                            account_index: 14,
+                            data_index: MembershipConfig::REQUIRED_MINT_OFFSET,
+                            length: 32,
+                        },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 16: đăng ký vault của chủ sở hữu account đích (nếu có)
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: VAULT_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                        // Chủ sở hữu của account token đích (index 2, bytes 32..64)
+                        Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 17: tổng số lượng đã chuyển trong epoch của owner nguồn
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: EPOCH_QUOTA_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                        // index 3: owner của account nguồn
+                        Seed::AccountKey { index: 3 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 18: bộ đếm địa chỉ nhận mới của owner nguồn
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: FAN_OUT_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                        // index 3: owner của account nguồn
+                        Seed::AccountKey { index: 3 },
+                    ],
+                    false,
+                    true
+                )?,
+                // index 19: program cấp attestation
+                ExtraAccountMeta::new_with_pubkey(&attestation_program.unwrap_or_default(), false, false)?,
+                // index 20: attestation của chủ sở hữu account đích, PDA của program ở index 19
+                ExtraAccountMeta::new_external_pda_with_seeds(
+                    19,
+                    &[
+                        Seed::Literal {
+                            bytes: ATTESTATION_SEED.to_vec(),
+                        },
+                        // Chủ sở hữu của account token đích (index 2, bytes 32..64)
+                        Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                    ],
+                    false,
+                    false
+                )?,
+                // index 21: treasury của mint, ghi nhận phí giao thức
+                ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: TREASURY_SEED.to_vec(),
+                        },
+                        // index 1: account mint
+                        Seed::AccountKey { index: 1 },
+                    ],
+                    false,
+                    true
+                )?
+            ]
+        )
+    }
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm Transfer Hook
+ * 
+ * QUAN TRỌNG: Thứ tự 4 account đầu tiên PHẢI theo đúng thứ tự:
+ * source_token, mint, destination_token, owner
+ * Đây là yêu cầu của Transfer Hook Interface
+ */
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    // account token nguồn
+    // Phải thỏa mãn: token::mint = mint, token::authority = owner
+    #[account(token::mint = mint, token::authority = owner)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account token đích
+    // Phải thỏa mãn: token::mint = mint
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: source token account owner, can be SystemAccount or PDA owned by another program
+    // Chủ sở hữu của account nguồn
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: ExtraAccountMetaList Account,
+    // account lưu trữ thông tin về các account bổ sung
+    // Được xác định bằng PDA từ seed "extra-account-metas" và địa chỉ mint
+    #[account(seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()], bump = white_list.meta_list_bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    // account lưu trữ whitelist
+    // Được xác định bằng PDA từ seeds "white_list" và địa chỉ mint
+    // mut: bộ đếm thống kê được cập nhật sau mỗi giao dịch thành công
+    #[account(mut, seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // account cấu hình token thành viên của mint
+    #[account(seeds = [MEMBERSHIP_SEED, mint.key().as_ref()], bump)]
+    pub membership_config: Account<'info, MembershipConfig>,
+    // Associated Token Program, dùng để suy ra account token thành viên
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: Destination owner's membership token account, may not exist, validated in check_membership_balance
+    // account token thành viên của chủ sở hữu account đích
+    // Có thể chưa tồn tại (khi đó số dư được coi là 0)
+    pub membership_token: UncheckedAccount<'info>,
+    /// CHECK: Release schedule PDA of the source token account, may not exist
+    // Lịch giải phóng của account nguồn, chỉ áp dụng khi account tồn tại
+    #[account(mut, seeds = [RELEASE_SEED, source_token.key().as_ref()], bump)]
+    pub source_release_schedule: UncheckedAccount<'info>,
+    /// CHECK: Release schedule PDA of the destination token account, may not exist
+    // Lịch giải phóng của account đích, chỉ áp dụng khi account tồn tại
+    #[account(mut, seeds = [RELEASE_SEED, destination_token.key().as_ref()], bump)]
+    pub destination_release_schedule: UncheckedAccount<'info>,
+    /// CHECK: Receiving limit PDA of the destination token account, may not exist
+    // Giới hạn nhận token của account đích, chỉ áp dụng khi account tồn tại
+    #[account(mut, seeds = [RECEIVING_LIMIT_SEED, destination_token.key().as_ref()], bump)]
+    pub destination_receiving_limit: UncheckedAccount<'info>,
+    /// CHECK: Merkle membership PDA of the destination owner, may not exist
+    // Kết quả xác minh Merkle của chủ sở hữu account đích, chỉ dùng ở chế độ MerkleRoot
+    #[account(seeds = [MERKLE_SEED, mint.key().as_ref(), destination_token.owner.as_ref()], bump)]
+    pub destination_merkle_membership: UncheckedAccount<'info>,
+    /// CHECK: Rate counter PDA of the source owner, must exist when rate limiting is enabled
+    // Bộ đếm giới hạn tần suất của owner nguồn
+    #[account(mut, seeds = [RATE_SEED, mint.key().as_ref(), owner.key().as_ref()], bump)]
+    pub source_rate_counter: UncheckedAccount<'info>,
+    // account lưu mint bắt buộc nắm giữ của mint
+    #[account(seeds = [REQUIRED_MINT_SEED, mint.key().as_ref()], bump)]
+    pub required_mint_config: Account<'info, MembershipConfig>,
+    /// CHECK: Destination owner's token account of the required mint, may not exist, validated in check_required_mint
+    // account token của chủ sở hữu account đích cho mint bắt buộc
+    pub required_mint_token: UncheckedAccount<'info>,
+    /// CHECK: Program vault registration PDA of the destination owner, may not exist
+    // Đăng ký vault của chủ sở hữu account đích, chỉ dùng khi có allowed_owner_programs
+    #[account(seeds = [VAULT_SEED, mint.key().as_ref(), destination_token.owner.as_ref()], bump)]
+    pub destination_program_vault: UncheckedAccount<'info>,
+    /// CHECK: Epoch quota PDA of the source owner, must exist when the epoch quota is enabled
+    // Tổng số lượng đã chuyển trong epoch của owner nguồn
+    #[account(mut, seeds = [EPOCH_QUOTA_SEED, mint.key().as_ref(), owner.key().as_ref()], bump)]
+    pub source_epoch_quota: UncheckedAccount<'info>,
+    /// CHECK: Fan-out counter PDA of the source owner, must exist when the fan-out limit is enabled
+    // Bộ đếm địa chỉ nhận mới của owner nguồn
+    #[account(mut, seeds = [FAN_OUT_SEED, mint.key().as_ref(), owner.key().as_ref()], bump)]
+    pub source_fan_out: UncheckedAccount<'info>,
+    /// CHECK: Attestation program configured on the white list, or the System Program placeholder
+    // Program cấp attestation, chỉ dùng ở chế độ Attestation
+    pub attestation_program: UncheckedAccount<'info>,
+    /// CHECK: Attestation PDA of the destination owner, validated in check_attestation
+    // Attestation của chủ sở hữu account đích, có thể chưa tồn tại
+    pub destination_attestation: UncheckedAccount<'info>,
+    /// CHECK: Treasury PDA of the mint, must exist when the fee is enabled
+    // Treasury của mint, ghi nhận phí giao thức
+    #[account(mut, seeds = [TREASURY_SEED, mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thêm vào whitelist
+ */
+#[derive(Accounts)]
+pub struct AddToWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: New account to add to white list
+    // Địa chỉ ví (owner) mới cần thêm vào whitelist
+    // Không cần kiểm tra gì về account này
+    #[account()]
+    pub new_account: AccountInfo<'info>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    // mut: account này sẽ bị chỉnh sửa (thêm địa chỉ mới)
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này sẽ trả phí giao dịch
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+impl<'info> AddToWhiteList<'info> {
+    /*
+     * Thêm new_account vào whitelist
+     *
+     * Dùng chung cho add_to_whitelist và add_to_whitelist_with_expiry
+     */
+    pub fn add(&mut self, expires_at: Option<i64>) -> Result<()> {
+        // Kiểm tra xem người ký giao dịch có phải là authority hoặc admin của whitelist không
+        // Đây là biện pháp bảo mật để đảm bảo chỉ người được ủy quyền mới có thể thay đổi whitelist
+        if !is_authorized_manager(&self.white_list, &self.mint.key(), &self.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        // Địa chỉ vừa bị xóa phải chờ hết cooldown_slots mới được thêm lại
+        let clock = Clock::get()?;
+        self.white_list.check_cooldown(&self.new_account.key(), clock.slot)?;
+
+        // Khi cần xác nhận, địa chỉ chỉ được đưa vào danh sách chờ
+        if self.white_list.require_confirmation {
+            self.white_list.propose_addition(PendingAddition {
+                account: self.new_account.key(),
+                proposer: self.signer.key(),
+                expires_at,
+            })?;
+            msg!("Addition pending confirmation: {0}", self.new_account.key().to_string());
+            emit!(AdditionProposed {
+                account: self.new_account.key(),
+                proposer: self.signer.key(),
+            });
+            return Ok(());
+        }
+
+        // Thêm địa chỉ mới vào whitelist, kèm thời điểm được thêm
+        // Không thêm địa chỉ đã có và không vượt quá max_entries và dung lượng hiện tại của account
+        let capacity = self.white_list.capacity(self.white_list.to_account_info().data_len());
+        self.white_list.add_entry(
+            WhiteListEntry {
+                account: self.new_account.key(),
+                added_at: clock.unix_timestamp,
+                expires_at,
+                tier: 0,
+                changed_slot: clock.slot,
+                suspended: false,
+            },
+            capacity
+        )?;
+        // Log thông tin về địa chỉ đã thêm
+        msg!("New account white listed! {0}", self.new_account.key().to_string());
+        // Log số lượng địa chỉ hiện có trong whitelist
+        msg!("White list length! {0}", self.white_list.white_list.len());
+        // Phát sự kiện để indexer theo dõi thay đổi của whitelist
+        emit!(WhitelistAdded {
+            account: self.new_account.key(),
+            authority: self.signer.key(),
+            length: self.white_list.white_list.len() as u32,
+        });
+
+        Ok(())
+    }
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xác nhận/hủy địa chỉ đang chờ
+ */
+#[derive(Accounts)]
+pub struct ConfirmAddition<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, chứa danh sách chờ xác nhận
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority hoặc admin của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt yêu cầu xác nhận
+ */
+#[derive(Accounts)]
+pub struct SetRequireConfirmation<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ require_confirmation
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thêm nhiều địa chỉ vào whitelist
+ */
+#[derive(Accounts)]
+pub struct AddManyToWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xóa khỏi whitelist
+ */
+#[derive(Accounts)]
+pub struct RemoveFromWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Account to remove from white list
+    // Địa chỉ ví (owner) cần xóa khỏi whitelist
+    // Không cần kiểm tra gì về account này
+    #[account()]
+    pub account_to_remove: AccountInfo<'info>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    // mut: account này sẽ bị chỉnh sửa (xóa địa chỉ)
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này sẽ trả phí giao dịch
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // Nhật ký xóa, bắt buộc khi record_removals được bật
+    #[account(
+        mut,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump = audit.bump
+    )]
+    pub audit: Option<Account<'info, RemovalAudit>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xóa nhiều địa chỉ khỏi whitelist
+ */
+#[derive(Accounts)]
+pub struct RemoveManyFromWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // Nhật ký xóa, bắt buộc khi record_removals được bật
+    #[account(
+        mut,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump = audit.bump
+    )]
+    pub audit: Option<Account<'info, RemovalAudit>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xóa địa chỉ theo vị trí
+ */
+#[derive(Accounts)]
+pub struct RemoveByIndex<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority hoặc admin của whitelist
+    pub signer: Signer<'info>,
+    // Nhật ký xóa, bắt buộc khi record_removals được bật
+    #[account(
+        mut,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump = audit.bump
+    )]
+    pub audit: Option<Account<'info, RemovalAudit>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xóa toàn bộ whitelist
+ */
+#[derive(Accounts)]
+pub struct ClearWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+    // Nhật ký xóa, bắt buộc khi record_removals được bật
+    #[account(
+        mut,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump = audit.bump
+    )]
+    pub audit: Option<Account<'info, RemovalAudit>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thay thế toàn bộ whitelist
+ */
+#[derive(Accounts)]
+pub struct ReplaceWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cần có quyền ghi để cập nhật
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+    // Nhật ký xóa, bắt buộc khi record_removals được bật
+    #[account(
+        mut,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump = audit.bump
+    )]
+    pub audit: Option<Account<'info, RemovalAudit>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạo nhật ký xóa
+ */
+#[derive(Accounts)]
+#[instruction(capacity: u16)]
+pub struct CreateRemovalAudit<'info> {
+    // account mint của token, mỗi mint có một nhật ký riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Nhật ký xóa, được tạo từ seeds "audit" và địa chỉ mint
+    #[account(
+        init,
+        seeds = [AUDIT_SEED, mint.key().as_ref()],
+        bump,
+        payer = signer,
+        space = RemovalAudit::space_for(capacity as usize)
+    )]
+    pub audit: Account<'info, RemovalAudit>,
+    // Người ký giao dịch, phải là authority của whitelist và trả phí tạo account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm xóa bớt bản ghi cũ của nhật ký xóa
+ */
+#[derive(Accounts)]
+pub struct PruneRemovalAudit<'info> {
+    // account mint của token, mỗi mint có một nhật ký riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Nhật ký xóa cần cập nhật
+    #[account(mut, seeds = [AUDIT_SEED, mint.key().as_ref()], bump = audit.bump)]
+    pub audit: Account<'info, RemovalAudit>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đọc nhật ký xóa
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct GetRemovalAudit<'info> {
+    // account mint của token, mỗi mint có một nhật ký riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Nhật ký xóa cần đọc
+    #[account(seeds = [AUDIT_SEED, mint.key().as_ref()], bump = audit.bump)]
+    pub audit: Account<'info, RemovalAudit>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm chụp ảnh whitelist
+ */
+#[derive(Accounts)]
+#[instruction(slot: u64)]
+pub struct SnapshotWhiteList<'info> {
+    // account mint của token, mỗi mint có các ảnh chụp riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần chụp
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Ảnh chụp, được tạo từ seeds "snapshot", địa chỉ mint và slot
+    // Kích thước vừa đủ cho các địa chỉ hiện có
+    #[account(
+        init,
+        seeds = [SNAPSHOT_SEED, mint.key().as_ref(), &slot.to_le_bytes()],
+        bump,
+        payer = signer,
+        space = WhitelistSnapshot::space_for(white_list.white_list.len())
+    )]
+    pub snapshot: Account<'info, WhitelistSnapshot>,
+    // Người ký giao dịch, phải là authority của whitelist và trả phí tạo account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đóng ảnh chụp whitelist
+ */
+#[derive(Accounts)]
+#[instruction(slot: u64)]
+pub struct CloseSnapshot<'info> {
+    // account mint của token, mỗi mint có các ảnh chụp riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Ảnh chụp cần đóng, rent được trả về cho signer
+    #[account(
+        mut,
+        seeds = [SNAPSHOT_SEED, mint.key().as_ref(), &slot.to_le_bytes()],
+        bump = snapshot.bump,
+        close = signer
+    )]
+    pub snapshot: Account<'info, WhitelistSnapshot>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này nhận lại rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đọc ảnh chụp whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+#[instruction(slot: u64)]
+pub struct GetSnapshot<'info> {
+    // account mint của token, mỗi mint có các ảnh chụp riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Ảnh chụp cần đọc
+    #[account(seeds = [SNAPSHOT_SEED, mint.key().as_ref(), &slot.to_le_bytes()], bump = snapshot.bump)]
+    pub snapshot: Account<'info, WhitelistSnapshot>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cấu hình token thành viên
+ */
+#[derive(Accounts)]
+pub struct SetMembershipRequirement<'info> {
+    // account mint của token có transfer hook
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Mint của token thành viên
+    // Token program sở hữu mint này được lưu lại để suy ra địa chỉ ATA
+    pub required_mint: InterfaceAccount<'info, Mint>,
+    // account cấu hình token thành viên của mint
+    #[account(
+        mut,
+        seeds = [MEMBERSHIP_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub membership_config: Account<'info, MembershipConfig>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt mint bắt buộc nắm giữ
+ */
+#[derive(Accounts)]
+pub struct SetRequiredMint<'info> {
+    // account mint của token có transfer hook
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Mint bắt buộc nắm giữ, bỏ trống để tắt yêu cầu
+    // Token program sở hữu mint này được lưu lại để suy ra địa chỉ ATA
+    pub required_mint: Option<InterfaceAccount<'info, Mint>>,
+    // account lưu mint bắt buộc, dùng để suy ra account token trong ExtraAccountMetaList
+    #[account(
+        mut,
+        seeds = [REQUIRED_MINT_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub required_mint_config: Account<'info, MembershipConfig>,
+    // account whitelist, lưu mint bắt buộc
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt lịch giải phóng cho escrow
+ */
+#[derive(Accounts)]
+#[instruction(account: Pubkey)]
+pub struct SetReleaseSchedule<'info> {
+    // account mint của token có transfer hook
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account token escrow, phải thuộc mint này
+    #[account(address = account, token::mint = mint)]
+    pub escrow_token: InterfaceAccount<'info, TokenAccount>,
+    // Lịch giải phóng của escrow
+    // Được tạo từ seeds "release" và địa chỉ account escrow
+    #[account(
+        init_if_needed,
+        seeds = [RELEASE_SEED, account.as_ref()],
+        bump,
+        payer = signer,
+        space = ReleaseSchedule::LEN
+    )]
+    pub release_schedule: Account<'info, ReleaseSchedule>,
+    // account whitelist, dùng để kiểm tra authority và escrow có trong whitelist
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí tạo account lịch giải phóng
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt giới hạn nhận token của account đích
+ */
+#[derive(Accounts)]
+#[instruction(account: Pubkey)]
+pub struct SetReceivingCap<'info> {
+    // account mint của token có transfer hook
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account token đích, phải thuộc mint này
+    #[account(address = account, token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+    // Giới hạn nhận token của account đích
+    // Được tạo từ seeds "recv_limit" và địa chỉ account đích
+    #[account(
+        init_if_needed,
+        seeds = [RECEIVING_LIMIT_SEED, account.as_ref()],
+        bump,
+        payer = signer,
+        space = ReceivingLimit::LEN
+    )]
+    pub receiving_limit: Account<'info, ReceivingLimit>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí tạo account giới hạn
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạo trước account theo dõi của account đích
+ */
+#[derive(Accounts)]
+#[instruction(destination: Pubkey)]
+pub struct InitDestinationRecord<'info> {
+    // account mint của token có transfer hook
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account token đích, phải thuộc mint này
+    #[account(address = destination, token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+    // account theo dõi số token nhận được của account đích
+    // Được tạo từ seeds "recv_limit" và địa chỉ account đích
+    // init_if_needed: account đã tồn tại được báo lỗi RecordAlreadyExists thay vì lỗi của System Program
+    #[account(
+        init_if_needed,
+        seeds = [RECEIVING_LIMIT_SEED, destination.as_ref()],
+        bump,
+        payer = signer,
+        space = ReceivingLimit::LEN
+    )]
+    pub receiving_limit: Account<'info, ReceivingLimit>,
+    // account whitelist, dùng để kiểm tra authority
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority hoặc chủ sở hữu account đích
+    // mut: account này trả phí tạo account
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt root của cây Merkle
+ */
+#[derive(Accounts)]
+pub struct SetMerkleRoot<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu merkle_root
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt program cấp attestation
+ */
+#[derive(Accounts)]
+pub struct SetAttestationProgram<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu attestation_program
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    // account PDA lưu trữ thông tin về các account bổ sung, được ghi lại với program mới
+    #[account(
+        mut,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump = white_list.meta_list_bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí rent khi ExtraAccountMetaList cần tăng kích thước
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để chuyển lamports
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm gửi bằng chứng Merkle
+ */
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct SubmitMerkleProof<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, chứa merkle_root hiện tại
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // Kết quả xác minh của owner
+    // Được tạo từ seeds "merkle", địa chỉ mint và địa chỉ owner
+    #[account(
+        init_if_needed,
+        seeds = [MERKLE_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump,
+        payer = payer,
+        space = MerkleMembership::LEN
+    )]
+    pub merkle_membership: Account<'info, MerkleMembership>,
+    // Người trả phí tạo account, có thể là bất kỳ ai (thường là chính owner)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cấu hình giới hạn tần suất
+ */
+#[derive(Accounts)]
+pub struct SetRateLimit<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cấu hình giới hạn tần suất
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cấu hình quota mỗi epoch
+ */
+#[derive(Accounts)]
+pub struct SetEpochQuota<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cấu hình quota mỗi epoch
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạo bộ đếm quota mỗi epoch
+ */
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct CreateEpochQuota<'info> {
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Bộ đếm quota của owner
+    // Được tạo từ seeds "epoch_quota", địa chỉ mint và địa chỉ owner
+    #[account(
+        init,
+        seeds = [EPOCH_QUOTA_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump,
+        payer = payer,
+        space = EpochQuota::LEN
+    )]
+    pub epoch_quota: Account<'info, EpochQuota>,
+    // Người trả phí tạo account, có thể là bất kỳ ai (thường là chính owner)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cấu hình phí giao thức
+ */
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    // account mint của token, mỗi mint có một whitelist và một treasury riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu mức phí
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Treasury của mint, được tạo ở lần cấu hình đầu tiên
+    #[account(
+        init_if_needed,
+        seeds = [TREASURY_SEED, mint.key().as_ref()],
+        bump,
+        payer = signer,
+        space = Treasury::LEN
+    )]
+    pub treasury: Account<'info, Treasury>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: trả phí tạo treasury
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetFee<'info> {
+    /*
+     * Lưu tỉ lệ phí sau khi kiểm tra quyền và giới hạn
+     *
+     * Dùng chung cho set_fee_basis_points và set_fee_rate
+     */
+    pub fn set(&mut self, fee_numerator: u32, fee_denominator: u32) -> Result<()> {
+        // Chỉ authority mới có thể cấu hình phí
+        if self.white_list.authority != self.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        WhiteList::validate_fee(fee_numerator, fee_denominator)?;
+
+        self.treasury.mint = self.mint.key();
+        self.white_list.fee_numerator = fee_numerator;
+        self.white_list.fee_denominator = fee_denominator;
+        msg!("Fee rate: {0}/{1}", fee_numerator, fee_denominator);
+
+        Ok(())
+    }
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cấu hình giới hạn số địa chỉ nhận mới
+ */
+#[derive(Accounts)]
+pub struct SetFanOutLimit<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cấu hình giới hạn số địa chỉ nhận mới
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạo bộ đếm địa chỉ nhận mới
+ */
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct CreateFanOutCounter<'info> {
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Bộ đếm của owner
+    // Được tạo từ seeds "fan_out", địa chỉ mint và địa chỉ owner
+    #[account(
+        init,
+        seeds = [FAN_OUT_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump,
+        payer = payer,
+        space = FanOutCounter::LEN
+    )]
+    pub fan_out_counter: Account<'info, FanOutCounter>,
+    // Người trả phí tạo account, có thể là bất kỳ ai (thường là chính owner)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạo bộ đếm giới hạn tần suất
+ */
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct CreateRateCounter<'info> {
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Bộ đếm của owner
+    // Được tạo từ seeds "rate", địa chỉ mint và địa chỉ owner
+    // Mỗi mint có cấu hình riêng nên bộ đếm cũng được tách theo mint
+    #[account(
+        init,
+        seeds = [RATE_SEED, mint.key().as_ref(), owner.as_ref()],
+        bump,
+        payer = payer,
+        space = RateCounter::LEN
+    )]
+    pub rate_counter: Account<'info, RateCounter>,
+    // Người trả phí tạo account, có thể là bất kỳ ai (thường là chính owner)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm kiểm tra cấu hình (health_check)
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần kiểm tra
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đọc cấu hình (get_config)
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần đọc cấu hình
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm kiểm tra một địa chỉ có trong whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct CheckWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần tra cứu
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm kiểm tra thành viên gọi qua CPI
+ *
+ * Chỉ cần account whitelist, program gọi tự suy ra PDA từ seeds
+ * ["white_list", mint] để chắc chắn đó là whitelist của đúng mint
+ */
+#[derive(Accounts)]
+pub struct CheckMembership<'info> {
+    // account whitelist cần tra cứu, Anchor kiểm tra account thuộc program này
+    #[account(constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm kiểm tra chủ sở hữu của một account token
+ * có trong whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct ContainsOwner<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account token (thường là ATA) cần tra cứu chủ sở hữu, phải thuộc mint này
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+    // account whitelist cần tra cứu
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm kiểm tra trước giao dịch chuyển token
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct CanTransfer<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist chứa các chính sách cần kiểm tra
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm lấy danh sách whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct GetWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần đọc
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm lấy số địa chỉ còn có thể thêm vào whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct RemainingCapacity<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần đọc
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm lấy một trang của whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct GetWhiteListPage<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần đọc
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cập nhật ExtraAccountMetaList
+ *
+ * Ba account đầu theo đúng thứ tự của instruction UpdateExtraAccountMetaList
+ * trong Transfer Hook Interface: meta list, mint, authority
+ */
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    // account PDA lưu trữ thông tin về các account bổ sung, được ghi lại toàn bộ
+    #[account(
+        mut,
+        seeds = [EXTRA_ACCOUNT_METAS_SEED, mint.key().as_ref()],
+        bump = white_list.meta_list_bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Người ký giao dịch, phải là meta_list_authority (mặc định là authority của whitelist)
+    // mut: account này trả phí rent khi ExtraAccountMetaList cần tăng kích thước
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // account whitelist của mint, dùng để kiểm tra quyền
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    // System Program, cần thiết để chuyển lamports
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm lấy kích thước của ExtraAccountMetaList
+ *
+ * Không cần account nào
+ */
+#[derive(Accounts)]
+pub struct ExtraAccountMetaListSize {}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tìm các địa chỉ chưa có trong whitelist
+ *
+ * Chỉ đọc, không cần signer
+ */
+#[derive(Accounts)]
+pub struct FindMissing<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần tra cứu
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thay đổi kích thước whitelist
+ */
+#[derive(Accounts)]
+#[instruction(new_capacity: u32)]
+pub struct ResizeWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, được thay đổi kích thước theo dung lượng mới trong hàm xử lý
+    // authority trả thêm rent khi tăng kích thước (hoặc nhận lại rent khi giảm kích thước)
+    // Lưu ý: runtime chỉ cho phép tăng tối đa 10240 bytes mỗi instruction
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí rent cho phần tăng thêm
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để chuyển lamports khi tăng kích thước
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thu hồi rent dư của whitelist
+ */
+#[derive(Accounts)]
+pub struct ReclaimSurplus<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lamports vượt quá mức miễn rent được chuyển ra
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này nhận lamports dư
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tăng dung lượng whitelist
+ */
+#[derive(Accounts)]
+pub struct GrowWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, được tăng kích thước trong instruction
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí rent cho phần tăng thêm
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để chuyển lamports
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đề xuất chuyển quyền authority
+ */
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu authority đang chờ
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority hiện tại
+    pub signer: Signer<'info>,
+    /// CHECK: Proposed authority, only its owner is inspected when verify_owner is supplied
+    // account của authority mới, bắt buộc khi truyền verify_owner
+    pub new_authority_account: Option<UncheckedAccount<'info>>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm chấp nhận quyền authority
+ */
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, cập nhật authority mới
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority đang chờ
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt tạm dừng chuyển token
+ */
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu trạng thái tạm dừng
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm cho phép/chặn tự chuyển token
+ */
+#[derive(Accounts)]
+pub struct SetAllowSelfTransfer<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ block_self_transfer
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt ghi nhận việc xóa
+ */
+#[derive(Accounts)]
+pub struct SetRecordRemovals<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ record_removals
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt thời gian chờ giữa hai lần thay đổi một địa chỉ
+ */
+#[derive(Accounts)]
+pub struct SetCooldownSlots<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu thời gian chờ
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt địa chỉ gửi được bỏ qua kiểm tra phía nhận
+ */
+#[derive(Accounts)]
+pub struct SetBypassSender<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu bypass_sender
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt chế độ so khớp phía nhận
+ */
+#[derive(Accounts)]
+pub struct SetMatchMode<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu chế độ so khớp
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm yêu cầu account token đích đã tồn tại
+ */
+#[derive(Accounts)]
+pub struct SetRequireExistingDestination<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ require_existing_destination
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt chống làm rỗng whitelist
+ */
+#[derive(Accounts)]
+pub struct SetPreventEmpty<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ prevent_empty
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt miễn kiểm tra giao dịch 0 token
+ */
+#[derive(Accounts)]
+pub struct SetAllowZeroAmount<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ allow_zero_amount
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt chế độ chỉ chuyển trong cùng một ví
+ */
+#[derive(Accounts)]
+pub struct SetSameOwnerOnly<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ same_owner_only
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt miễn kiểm tra khi đốt token
+ */
+#[derive(Accounts)]
+pub struct SetAllowBurn<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ allow_burn
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt sự kiện TransferObserved
+ */
+#[derive(Accounts)]
+pub struct SetVerboseLogging<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ verbose_logging
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm bật/tắt kiểm tra phía gửi
+ */
+#[derive(Accounts)]
+pub struct SetCheckSource<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu cờ check_source
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đóng whitelist
+ */
+#[derive(Accounts)]
+pub struct CloseWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần đóng
+    // close = signer: toàn bộ lamports (rent) được trả về cho signer
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired,
+        close = signer
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này nhận lại rent
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đổi chế độ danh sách
+ */
+#[derive(Accounts)]
+pub struct SetMode<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu chế độ danh sách
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt số lượng tối thiểu mỗi giao dịch
+ */
+#[derive(Accounts)]
+pub struct SetMinTransferAmount<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu số lượng tối thiểu
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt cấp của một địa chỉ trong whitelist
+ */
+#[derive(Accounts)]
+pub struct SetEntryTier<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist chứa địa chỉ cần đặt cấp
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm tạm ngưng/khôi phục một địa chỉ trong whitelist
+ */
+#[derive(Accounts)]
+pub struct SuspendAddress<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist chứa địa chỉ cần tạm ngưng
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt giới hạn số lượng theo cấp
+ */
+#[derive(Accounts)]
+pub struct SetTierLimits<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu giới hạn theo cấp
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt giới hạn số lượng mỗi giao dịch
+ */
+#[derive(Accounts)]
+pub struct SetMaxTransferAmount<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu giới hạn số lượng
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt thời gian chờ kích hoạt
+ */
+#[derive(Accounts)]
+pub struct SetActivationDelay<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu thời gian chờ kích hoạt
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt tên whitelist
+ */
+#[derive(Accounts)]
+pub struct SetName<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu tên hiển thị
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm nâng cấp layout của whitelist
+ *
+ * white_list được khai báo là UncheckedAccount vì account cũ có thể
+ * không đủ kích thước để deserialize theo layout mới
+ */
+#[derive(Accounts)]
+pub struct UpgradeWhiteListLayout<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: White list PDA, discriminator and authority are verified in the instruction
+    // account whitelist cần nâng cấp
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub white_list: UncheckedAccount<'info>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí rent cho phần tăng thêm
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để chuyển lamports
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm chuyển đổi whitelist sang phiên bản hiện tại
+ *
+ * white_list được khai báo là UncheckedAccount vì account phiên bản cũ
+ * không deserialize được theo layout mới
+ */
+#[derive(Accounts)]
+pub struct MigrateWhiteListVersion<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: White list PDA, discriminator, version and authority are verified in the instruction
+    // account whitelist cần chuyển đổi
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub white_list: UncheckedAccount<'info>,
+    // Người ký giao dịch, phải là authority của whitelist
+    // mut: account này trả phí rent cho phần tăng thêm
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để chuyển lamports
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm sắp xếp lại whitelist
+ */
+#[derive(Accounts)]
+pub struct SortWhiteList<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist cần sắp xếp
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt authority là PDA của program quản trị
+ */
+#[derive(Accounts)]
+pub struct SetAuthorityPda<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu authority và cách suy ra PDA
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority hiện tại của whitelist
+    pub signer: Signer<'info>,
+    /// CHECK: New authority PDA, verified against the derivation from program_id and seeds
+    // PDA sẽ trở thành authority mới
+    pub authority_pda: UncheckedAccount<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt địa chỉ quản lý ExtraAccountMetaList
+ */
+#[derive(Accounts)]
+pub struct UpdateMetaListAuthority<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu meta_list_authority
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đặt program authority
+ */
+#[derive(Accounts)]
+pub struct SetProgramAuthority<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu program_authority
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thêm/xóa admin
+ */
+#[derive(Accounts)]
+pub struct ManageAdmins<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu danh sách admin
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm thêm/xóa program được phép
+ */
+#[derive(Accounts)]
+pub struct ManageOwnerPrograms<'info> {
+    // account mint của token, mỗi mint có một whitelist riêng
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, lưu danh sách program được phép
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump = white_list.white_list_bump,
+        constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist
+    pub signer: Signer<'info>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm đăng ký vault của program được phép
+ */
+#[derive(Accounts)]
+pub struct RegisterProgramVault<'info> {
+    // account mint của token
+    pub mint: InterfaceAccount<'info, Mint>,
+    // account whitelist, chứa danh sách program được phép
+    #[account(seeds = [WHITE_LIST_SEED, mint.key().as_ref()], bump = white_list.white_list_bump, constraint = white_list.version == WhiteList::VERSION @ TransferError::MigrationRequired)]
+    pub white_list: Account<'info, WhiteList>,
+    /// CHECK: Vault address, only its owner program is inspected
+    // Địa chỉ vault cần đăng ký (chủ sở hữu của account token nhận)
+    pub vault: UncheckedAccount<'info>,
+    // Đăng ký của vault
+    // Được tạo từ seeds "vault", địa chỉ mint và địa chỉ vault
+    #[account(
+        init_if_needed,
+        seeds = [VAULT_SEED, mint.key().as_ref(), vault.key().as_ref()],
+        bump,
+        payer = payer,
+        space = ProgramVault::LEN
+    )]
+    pub program_vault: Account<'info, ProgramVault>,
+    // Người trả phí tạo account, có thể là bất kỳ ai
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+/*
+ * Định nghĩa cấu trúc account cho hàm di chuyển whitelist toàn cục cũ
+ * sang whitelist theo từng mint
+ */
+#[derive(Accounts)]
+pub struct MigrateWhiteList<'info> {
+    // account mint của token, whitelist mới thuộc về mint này
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Legacy global white list PDA, discriminator and authority are verified in the instruction
+    // account whitelist toàn cục cũ
+    #[account(
+        mut,
+        seeds = [WHITE_LIST_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub legacy_white_list: UncheckedAccount<'info>,
+    // account whitelist mới của mint
+    // init_if_needed: Cho phép gọi lại sau khi lần trước thất bại giữa chừng
+    // space: Đủ chỗ cho toàn bộ địa chỉ của whitelist cũ
+    #[account(
+        init_if_needed,
+        seeds = [WHITE_LIST_SEED, mint.key().as_ref()],
+        bump,
+        payer = signer,
+        space = WhiteList::space_for(
+            LegacyWhiteList::try_from_account(&legacy_white_list)?.white_list.len()
+        )
+    )]
+    pub white_list: Account<'info, WhiteList>,
+    // Người ký giao dịch, phải là authority của whitelist cũ
+    // mut: trả phí tạo account mới và nhận lại rent khi đóng account cũ
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // System Program, cần thiết để tạo account
+    pub system_program: Program<'info, System>,
+}
+
+// PHẦN 5: MODULE CHƯƠNG TRÌNH CHÍNH
+// Định nghĩa các hàm xử lý (entry points) của smart contract
+#[program]
+pub mod transfer_hook {
+    use super::*;
+
+    /*
+     * Hàm khởi tạo ExtraAccountMetaList
+     * 
+     * Đây là hàm bắt buộc phải triển khai theo Transfer Hook Interface
+     * Mục đích: Tạo và khởi tạo account ExtraAccountMetaList chứa thông tin
+     * về các account bổ sung cần được cung cấp khi thực hiện chuyển token
+     * 
+     * QUAN TRỌNG: Hàm này phải được gọi trước khi có thể sử dụng transfer hook
+     * max_entries: Số địa chỉ tối đa của whitelist, quyết định kích thước account
+     * Lưu ý: whitelist dùng init_if_needed để tương thích với initialize_whitelist và
+     * migrate_whitelist, nên gọi hai lần không báo lỗi mà dùng lại whitelist đã có.
+     * Triển khai mới nên dùng initialize_extra_account_meta_list_strict
+     */
+    #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+        max_entries: u16
+    ) -> Result<()> {
+        ctx.accounts.initialize(max_entries, &ctx.bumps)
+    }
+
+    /*
+     * Hàm khởi tạo ExtraAccountMetaList chặt chẽ (khuyến nghị cho triển khai mới)
+     *
+     * Mục đích: Đảm bảo whitelist chỉ được khởi tạo một lần. Khác với
+     * initialize_extra_account_meta_list (dùng init_if_needed cho whitelist),
+     * giao dịch thất bại ngay nếu whitelist đã tồn tại, nên lỗi gọi khởi tạo hai lần
+     * của client được phát hiện thay vì âm thầm dùng lại whitelist cũ
+     * payer trở thành authority của whitelist
+     */
+    pub fn initialize_extra_account_meta_list_strict(
+        ctx: Context<InitializeExtraAccountMetaListStrict>,
+        max_entries: u16
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        accounts.white_list.initialize(
+            accounts.payer.key(),
+            max_entries,
+            ctx.bumps.white_list,
+            ctx.bumps.extra_account_meta_list
+        );
+        emit!(WhitelistInitialized {
+            mint: accounts.mint.key(),
+            authority: accounts.payer.key(),
+            max_entries,
+        });
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &InitializeExtraAccountMetaList::extra_account_metas(None)?
+        )?;
+        msg!("White list initialized for {0}", accounts.payer.key().to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm chỉ khởi tạo whitelist
+     *
+     * Mục đích: Tách việc tạo whitelist khỏi ExtraAccountMetaList để có thể
+     * chuẩn bị whitelist trước khi kích hoạt transfer hook
+     * initialize_extra_account_meta_list gọi sau đó sẽ giữ nguyên whitelist này
+     * authority: Địa chỉ quản lý whitelist, có thể khác với payer (ví dụ multisig)
+     */
+    pub fn initialize_whitelist(
+        ctx: Context<InitializeWhiteList>,
+        authority: Pubkey,
+        max_entries: u16
+    ) -> Result<()> {
+        let meta_list_bump = WhiteList::find_meta_list_bump(&ctx.accounts.mint.key());
+        ctx.accounts.white_list.initialize(authority, max_entries, ctx.bumps.white_list, meta_list_bump);
+        emit!(WhitelistInitialized {
+            mint: ctx.accounts.mint.key(),
+            authority,
+            max_entries,
+        });
+        msg!("White list initialized for {0}", authority.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm khởi tạo ExtraAccountMetaList kèm danh sách địa chỉ ban đầu
+     *
+     * Mục đích: Giống initialize_extra_account_meta_list nhưng thêm sẵn
+     * initial_accounts vào whitelist trong cùng một giao dịch, tránh khoảng
+     * thời gian hook đã hoạt động nhưng chưa cho phép ai nhận token
+     * Trả về lỗi WhitelistFull nếu vượt quá dung lượng của account
+     */
+    pub fn initialize_with_accounts(
+        ctx: Context<InitializeExtraAccountMetaList>,
+        max_entries: u16,
+        initial_accounts: Vec<Pubkey>
+    ) -> Result<()> {
+        ctx.accounts.initialize(max_entries, &ctx.bumps)?;
+        // Chỉ authority mới được thêm địa chỉ vào whitelist đã tồn tại
+        if ctx.accounts.white_list.authority != ctx.accounts.payer.key() {
+            msg!("White list already initialized by {0}", ctx.accounts.white_list.authority.to_string());
+            return err!(TransferError::AlreadyInitialized);
+        }
+
+        let capacity = ctx.accounts.white_list.capacity(ctx.accounts.white_list.to_account_info().data_len());
+        let clock = Clock::get()?;
+        let added_at = clock.unix_timestamp;
+        let authority = ctx.accounts.payer.key();
+        let white_list = &mut ctx.accounts.white_list;
+        for account in initial_accounts {
+            // Bỏ qua địa chỉ bị lặp trong đầu vào
+            if white_list.contains(&account) {
+                continue;
+            }
+            white_list.push_entry(WhiteListEntry { account, added_at, expires_at: None, tier: 0, changed_slot: clock.slot, suspended: false }, capacity)?;
+            emit!(WhitelistAdded {
+                account,
+                authority,
+                length: white_list.white_list.len() as u32,
+            });
+        }
+        msg!("White list length! {0}", white_list.white_list.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cập nhật ExtraAccountMetaList
+     *
+     * Mục đích: Ghi lại danh sách account bổ sung theo extra_account_metas() hiện tại,
+     * dùng sau khi nâng cấp program thêm account mới cho transfer hook
+     * Dùng discriminator của UpdateExtraAccountMetaList trong Transfer Hook Interface
+     * (#[interface] của Anchor chưa hỗ trợ instruction này) nên các công cụ chuẩn
+     * của SPL cũng gọi được
+     * Account được tăng hoặc giảm kích thước cho vừa danh sách mới,
+     * authority trả thêm rent khi cần
+     */
+    #[instruction(discriminator = UpdateExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        // Chỉ meta_list_authority (mặc định là authority) mới có thể thay đổi danh sách account bổ sung
+        if ctx.accounts.white_list.meta_list_manager() != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        rewrite_extra_account_meta_list(
+            &ctx.accounts.extra_account_meta_list.to_account_info(),
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program,
+            ctx.accounts.white_list.attestation_program
+        )
+    }
+
+    /*
+     * Hàm Transfer Hook chính
+     * 
+     * Đây là hàm bắt buộc phải triển khai theo Transfer Hook Interface
+     * Mục đích: Được Token-2022 tự động gọi khi có lệnh chuyển token
+     * 
+     * QUAN TRỌNG: Hàm này được gọi tự động, không cần gọi trực tiếp
+     * Hàm này quyết định việc chuyển token có thành công hay không
+     */
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+        // Thực hiện toàn bộ các kiểm tra, giữ lại kết quả để phát sự kiện
+        // Nếu được cho phép thì cập nhật bộ đếm thống kê của whitelist
+        let result = evaluate_transfer(&ctx, amount)
+            .and_then(|_| ctx.accounts.white_list.record_transfer(amount));
+
+        // Phát sự kiện TransferDecision cho cả hai trường hợp cho phép/từ chối
+        let clock = Clock::get()?;
+        emit!(TransferDecision {
+            source: ctx.accounts.source_token.key(),
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+            decision: if result.is_ok() { Decision::Allow } else { Decision::Reject },
+            reason_code: result.as_ref().err().map_or(0, error_code_of),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        if result.is_ok() && ctx.accounts.white_list.verbose_logging {
+            emit!(TransferObserved {
+                source: ctx.accounts.source_token.key(),
+                destination: ctx.accounts.destination_token.key(),
+                amount,
+                slot: clock.slot,
+            });
+        }
+
+        result
+    }
+
+    /*
+     * Hàm thêm địa chỉ vào whitelist
+     * 
+     * Mục đích: Cho phép authority thêm một địa chỉ mới vào whitelist
+     * Sau khi thêm, địa chỉ này sẽ có thể nhận được token thông qua chuyển token
+     */
+    pub fn add_to_whitelist(ctx: Context<AddToWhiteList>) -> Result<()> {
+        ctx.accounts.add(None)
+    }
+
+    /*
+     * Hàm thêm địa chỉ vào whitelist với thời hạn
+     *
+     * Mục đích: Cấp quyền nhận token tạm thời, tự động hết hiệu lực tại expires_at
+     * mà không cần authority nhớ xóa địa chỉ khỏi whitelist
+     */
+    pub fn add_to_whitelist_with_expiry(ctx: Context<AddToWhiteList>, expires_at: i64) -> Result<()> {
+        if expires_at <= Clock::get()?.unix_timestamp {
+            return err!(TransferError::InvalidExpiry);
+        }
+        ctx.accounts.add(Some(expires_at))
+    }
+
+    /*
+     * Hàm thêm nhiều địa chỉ vào whitelist trong một giao dịch
+     *
+     * Mục đích: Giảm số lượng giao dịch khi cần thêm một danh sách lớn địa chỉ
+     * Các địa chỉ đã có trong whitelist (hoặc bị lặp trong đầu vào) sẽ được bỏ qua
+     * Trả về lỗi WhitelistFull nếu không đủ chỗ thay vì cắt bớt danh sách
+     */
+    pub fn add_many_to_whitelist(
+        ctx: Context<AddManyToWhiteList>,
+        accounts: Vec<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority hoặc admin mới có thể thêm vào whitelist
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        // Dung lượng tối đa theo max_entries và kích thước hiện tại của account
+        let capacity = ctx.accounts.white_list.capacity(ctx.accounts.white_list.to_account_info().data_len());
+        let clock = Clock::get()?;
+        let added_at = clock.unix_timestamp;
+        let white_list = &mut ctx.accounts.white_list;
+        // Bỏ qua địa chỉ đã có hoặc bị lặp trong đầu vào để tránh trùng lặp
+        let mut new_accounts: Vec<Pubkey> = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            if !white_list.contains(&account) && !new_accounts.contains(&account) {
+                // Địa chỉ vừa bị xóa phải chờ hết cooldown_slots mới được thêm lại
+                white_list.check_cooldown(&account, clock.slot)?;
+                new_accounts.push(account);
+            }
+        }
+        // Khi cần xác nhận, các địa chỉ chỉ được đưa vào danh sách chờ
+        if white_list.require_confirmation {
+            for account in &new_accounts {
+                white_list.propose_addition(PendingAddition { account: *account, proposer: ctx.accounts.signer.key(), expires_at: None })?;
+                emit!(AdditionProposed {
+                    account: *account,
+                    proposer: ctx.accounts.signer.key(),
+                });
+            }
+            msg!("{0} addition(s) pending confirmation", new_accounts.len());
+            return Ok(());
+        }
+        // Kiểm tra đủ chỗ cho toàn bộ danh sách trước khi thêm
+        white_list.ensure_room(new_accounts.len(), capacity)?;
+        let mut added = 0;
+        for account in new_accounts {
+            white_list.push_entry(WhiteListEntry { account, added_at, expires_at: None, tier: 0, changed_slot: clock.slot, suspended: false }, capacity)?;
+            added += 1;
+            emit!(WhitelistAdded {
+                account,
+                authority: ctx.accounts.signer.key(),
+                length: white_list.white_list.len() as u32,
+            });
+        }
+
+        // Log số lượng địa chỉ thực sự được thêm và độ dài hiện tại
+        msg!("Added {0} account(s) to white list", added);
+        msg!("White list length! {0}", white_list.white_list.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xác nhận một địa chỉ đang chờ
+     *
+     * Mục đích: Kiểm soát kép (nguyên tắc bốn mắt): địa chỉ do một admin đề xuất
+     * chỉ được thêm vào whitelist khi một admin khác xác nhận
+     * Trả về lỗi SameSignerNotAllowed nếu người xác nhận cũng là người đề xuất
+     * Lưu ý: activation_delay được tính từ thời điểm xác nhận
+     */
+    pub fn confirm_addition(ctx: Context<ConfirmAddition>, account: Pubkey) -> Result<()> {
+        // Chỉ authority hoặc admin mới có thể xác nhận
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let capacity = ctx.accounts.white_list.capacity(ctx.accounts.white_list.to_account_info().data_len());
+        let clock = Clock::get()?;
+        let white_list = &mut ctx.accounts.white_list;
+        let pending = white_list.take_pending_addition(&account, &ctx.accounts.signer.key())?;
+        white_list.check_cooldown(&account, clock.slot)?;
+        white_list.add_entry(
+            WhiteListEntry {
+                account,
+                added_at: clock.unix_timestamp,
+                expires_at: pending.expires_at,
+                tier: 0,
+                changed_slot: clock.slot,
+                suspended: false,
+            },
+            capacity
+        )?;
+        msg!("Addition confirmed: {0}, proposed by {1}", account.to_string(), pending.proposer.to_string());
+        msg!("White list length! {0}", white_list.white_list.len());
+        emit!(WhitelistAdded {
+            account,
+            authority: ctx.accounts.signer.key(),
+            length: white_list.white_list.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /*
+     * Hàm hủy một địa chỉ đang chờ xác nhận
+     *
+     * Mục đích: Từ chối một đề xuất và giải phóng chỗ trong danh sách chờ
+     * Bất kỳ authority hoặc admin nào cũng có thể hủy, kể cả người đề xuất
+     */
+    pub fn cancel_addition(ctx: Context<ConfirmAddition>, account: Pubkey) -> Result<()> {
+        // Chỉ authority hoặc admin mới có thể hủy
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        match white_list.pending_additions.iter().position(|pending| pending.account == account) {
+            Some(index) => {
+                white_list.pending_additions.remove(index);
+                msg!("Pending addition cancelled: {0}", account.to_string());
+                Ok(())
+            }
+            None => err!(TransferError::PendingAdditionNotFound),
+        }
+    }
+
+    /*
+     * Hàm bật/tắt yêu cầu xác nhận khi thêm địa chỉ
+     *
+     * Mục đích: Với các thao tác cần độ tin cậy cao, mỗi địa chỉ mới phải được
+     * hai admin khác nhau phê duyệt mà không cần multisig bên ngoài
+     * Lưu ý: Các địa chỉ đang chờ được giữ nguyên khi tắt, vẫn cần confirm_addition
+     */
+    pub fn set_require_confirmation(ctx: Context<SetRequireConfirmation>, require_confirmation: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.require_confirmation = require_confirmation;
+        msg!("Require confirmation: {0}", require_confirmation);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa địa chỉ khỏi whitelist
+     * 
+     * Mục đích: Cho phép authority xóa một địa chỉ khỏi whitelist
+     * Sau khi xóa, địa chỉ này không thể nhận được token thông qua chuyển token
+     */
+    pub fn remove_from_whitelist(ctx: Context<RemoveFromWhiteList>) -> Result<()> {
+        // Kiểm tra xem người ký giao dịch có phải là authority hoặc admin của whitelist không
+        // Đây là biện pháp bảo mật để đảm bảo chỉ người được ủy quyền mới có thể thay đổi whitelist
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        // Lấy địa chỉ cần xóa
+        let account_key = ctx.accounts.account_to_remove.key();
+        // Xóa địa chỉ khỏi whitelist
+        // Trả về lỗi AccountNotFound nếu không tìm thấy địa chỉ trong whitelist
+        // Địa chỉ vừa được thêm phải chờ hết cooldown_slots mới được xóa
+        ctx.accounts.white_list.remove_account(&account_key, Clock::get()?.slot)?;
+        // Không được xóa địa chỉ cuối cùng nếu prevent_empty được bật
+        ctx.accounts.white_list.check_not_empty()?;
+        // Ghi vào nhật ký xóa nếu được bật
+        record_removals(&ctx.accounts.white_list, &mut ctx.accounts.audit, &[account_key])?;
+        // Log thông tin về địa chỉ đã xóa
+        msg!("Account removed from whitelist: {0}", account_key.to_string());
+        // Log số lượng địa chỉ còn lại trong whitelist
+        msg!("White list length: {0}", ctx.accounts.white_list.white_list.len());
+        // Phát sự kiện để indexer theo dõi thay đổi của whitelist
+        emit!(WhitelistRemoved {
+            account: account_key,
+            authority: ctx.accounts.signer.key(),
+            length: ctx.accounts.white_list.white_list.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa nhiều địa chỉ khỏi whitelist trong một giao dịch
+     *
+     * Mục đích: Cho phép thu hồi nhanh một danh sách địa chỉ (ví dụ khi có sự cố bảo mật)
+     * Các địa chỉ không có trong whitelist sẽ được bỏ qua thay vì hủy cả giao dịch
+     */
+    pub fn remove_many_from_whitelist(
+        ctx: Context<RemoveManyFromWhiteList>,
+        accounts: Vec<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority hoặc admin mới có thể xóa khỏi whitelist
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let slot = Clock::get()?.slot;
+        let white_list = &mut ctx.accounts.white_list;
+        let mut removed = Vec::new();
+        for account in accounts {
+            // Bỏ qua địa chỉ không có trong whitelist
+            if white_list.contains(&account) {
+                // Địa chỉ vừa được thêm phải chờ hết cooldown_slots mới được xóa
+                white_list.remove_account(&account, slot)?;
+                removed.push(account);
+                emit!(WhitelistRemoved {
+                    account,
+                    authority: ctx.accounts.signer.key(),
+                    length: white_list.white_list.len() as u32,
+                });
+            }
+        }
+        // Không được xóa địa chỉ cuối cùng nếu prevent_empty được bật
+        white_list.check_not_empty()?;
+        // Ghi vào nhật ký xóa nếu được bật
+        record_removals(white_list, &mut ctx.accounts.audit, &removed)?;
+
+        // Log số lượng địa chỉ thực sự bị xóa và độ dài còn lại
+        msg!("Removed {0} account(s) from white list", removed.len());
+        msg!("White list length: {0}", white_list.white_list.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa địa chỉ khỏi whitelist theo vị trí
+     *
+     * Mục đích: Tránh phải tìm kiếm tuần tự khi client đã biết vị trí (ví dụ từ get_whitelist)
+     * expected: Nếu có, địa chỉ tại vị trí phải trùng khớp, tránh xóa nhầm khi
+     * whitelist đã thay đổi kể từ lần đọc trước
+     * Lưu ý: Các phần tử phía sau được dịch lên để giữ thứ tự sắp xếp,
+     * client dựa vào vị trí cần đọc lại whitelist sau mỗi lần xóa
+     */
+    pub fn remove_from_whitelist_by_index(
+        ctx: Context<RemoveByIndex>,
+        index: u32,
+        expected: Option<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority hoặc admin mới có thể xóa khỏi whitelist
+        if !is_authorized_manager(&ctx.accounts.white_list, &ctx.accounts.mint.key(), &ctx.accounts.signer.key()) {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        let index = index as usize;
+        if index >= white_list.white_list.len() {
+            msg!("Index {0} out of bounds, white list has {1} entries", index, white_list.white_list.len());
+            return err!(TransferError::IndexOutOfBounds);
+        }
+        let account = white_list.white_list[index].account;
+        if expected.is_some_and(|expected| expected != account) {
+            msg!("Account at index {0} is {1}", index, account.to_string());
+            return err!(TransferError::EntryMismatch);
+        }
+
+        // Địa chỉ vừa được thêm phải chờ hết cooldown_slots mới được xóa
+        let slot = Clock::get()?.slot;
+        white_list.check_cooldown(&account, slot)?;
+        record_removals(white_list, &mut ctx.accounts.audit, &[account])?;
+        white_list.white_list.remove(index);
+        white_list.note_removal(account, slot);
+        // Không được xóa địa chỉ cuối cùng nếu prevent_empty được bật
+        white_list.check_not_empty()?;
+        msg!("Account removed from whitelist: {0}", account.to_string());
+        msg!("White list length: {0}", white_list.white_list.len());
+        emit!(WhitelistRemoved {
+            account,
+            authority: ctx.accounts.signer.key(),
+            length: white_list.white_list.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa toàn bộ địa chỉ trong whitelist
+     *
+     * Mục đích: Đặt lại whitelist trong một lệnh duy nhất (ví dụ khi chuyển sang chính sách mới)
+     * Dung lượng account được giữ nguyên để có thể thêm lại địa chỉ ngay sau đó
+     */
+    pub fn clear_whitelist(ctx: Context<ClearWhiteList>) -> Result<()> {
+        // Chỉ authority mới có thể xóa whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let cleared = ctx.accounts.white_list.white_list.len() as u32;
+        // Ghi toàn bộ địa chỉ vào nhật ký xóa nếu được bật
+        let removed: Vec<Pubkey> = ctx.accounts.white_list.white_list.iter().map(|entry| entry.account).collect();
+        record_removals(&ctx.accounts.white_list, &mut ctx.accounts.audit, &removed)?;
+        ctx.accounts.white_list.white_list = Vec::new();
+        // prevent_empty phải được tắt trước khi xóa toàn bộ whitelist
+        ctx.accounts.white_list.check_not_empty()?;
+
+        emit!(WhitelistCleared {
+            authority: ctx.accounts.signer.key(),
+            cleared,
+        });
+        msg!("Cleared {0} account(s) from white list", cleared);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm thay thế toàn bộ whitelist bằng một danh sách mới
+     *
+     * Mục đích: Đồng bộ whitelist với một nguồn dữ liệu bên ngoài trong một
+     * giao dịch duy nhất, không cần tự tính phần thêm/xóa ở phía client
+     * Địa chỉ bị lặp trong đầu vào được bỏ qua
+     * Trả về lỗi WhitelistFull nếu danh sách mới vượt quá dung lượng
+     */
+    pub fn replace_whitelist(ctx: Context<ReplaceWhiteList>, accounts: Vec<Pubkey>) -> Result<()> {
+        // Chỉ authority mới có thể thay thế whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        // Thay thế có thể thêm địa chỉ mới mà không qua xác nhận
+        if ctx.accounts.white_list.require_confirmation {
+            return err!(TransferError::ConfirmationRequired);
+        }
+
+        let capacity = ctx.accounts.white_list.capacity(ctx.accounts.white_list.to_account_info().data_len());
+        let clock = Clock::get()?;
+        let removed = ctx.accounts.white_list.replace_entries(&accounts, clock.unix_timestamp, clock.slot, capacity)?;
+        // Danh sách mới rỗng bị từ chối nếu prevent_empty được bật
+        ctx.accounts.white_list.check_not_empty()?;
+        // Ghi các địa chỉ bị loại vào nhật ký xóa nếu được bật
+        record_removals(&ctx.accounts.white_list, &mut ctx.accounts.audit, &removed)?;
+
+        let length = ctx.accounts.white_list.white_list.len() as u32;
+        emit!(WhitelistReplaced {
+            authority: ctx.accounts.signer.key(),
+            length,
+        });
+        msg!("White list replaced, {0} account(s), {1} removed", length, removed.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tạo nhật ký xóa của whitelist
+     *
+     * Mục đích: Lưu lại địa chỉ nào bị mất quyền nhận token và khi nào
+     * (yêu cầu lưu trữ hồ sơ cho mục đích kiểm toán)
+     * capacity: Số bản ghi tối đa của nhật ký
+     * Lưu ý: Cần bật record_removals (set_record_removals) để bắt đầu ghi
+     */
+    pub fn create_removal_audit(ctx: Context<CreateRemovalAudit>, capacity: u16) -> Result<()> {
+        // Chỉ authority mới có thể tạo nhật ký
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.audit.bump = ctx.bumps.audit;
+        msg!("Removal audit log created with capacity {0}", capacity);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt ghi nhận các địa chỉ bị xóa khỏi whitelist
+     *
+     * Khi bật, các hàm xóa phải truyền account nhật ký xóa (audit),
+     * nếu không giao dịch sẽ thất bại với lỗi AuditLogRequired
+     */
+    pub fn set_record_removals(ctx: Context<SetRecordRemovals>, record_removals: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.record_removals = record_removals;
+        msg!("Record removals: {0}", record_removals);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa các bản ghi cũ của nhật ký xóa
+     *
+     * Mục đích: Giải phóng chỗ khi nhật ký đầy, chỉ xóa các bản ghi
+     * có thời điểm xóa trước before (sau khi đã được lưu trữ ở nơi khác)
+     */
+    pub fn prune_removal_audit(ctx: Context<PruneRemovalAudit>, before: i64) -> Result<()> {
+        // Chỉ authority mới có thể xóa bản ghi
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let pruned = ctx.accounts.audit.prune(before);
+        msg!("Pruned {0} removal record(s), {1} remaining", pruned, ctx.accounts.audit.records.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đọc nhật ký xóa theo trang
+     *
+     * Trả về các bản ghi records[offset..offset + limit] qua return data
+     * (gọi bằng simulateTransaction), offset vượt quá cuối trả về danh sách rỗng
+     * Lưu ý: limit lớn hơn MAX_RETURN_DATA_RECORDS được giảm xuống MAX_RETURN_DATA_RECORDS
+     */
+    pub fn get_removal_audit(ctx: Context<GetRemovalAudit>, offset: u32, limit: u32) -> Result<Vec<RemovalRecord>> {
+        Ok(ctx.accounts.audit.page(offset, limit))
+    }
+
+    /*
+     * Hàm chụp ảnh whitelist tại slot hiện tại
+     *
+     * Mục đích: Lưu lại lịch sử whitelist trên chain, không thể sửa đổi, để kiểm toán viên
+     * xác minh danh sách tại một thời điểm trong quá khứ
+     * slot: Slot dùng trong seeds của ảnh chụp, phải là slot hiện tại hoặc không cũ hơn
+     * MAX_SLOT_AGE (client thường dùng slot lúc tạo giao dịch)
+     * Trả về lỗi SnapshotTooLarge nếu whitelist có nhiều hơn MAX_ACCOUNTS địa chỉ
+     */
+    pub fn snapshot_whitelist(ctx: Context<SnapshotWhiteList>, slot: u64) -> Result<()> {
+        // Chỉ authority mới có thể chụp ảnh (trả phí rent)
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let clock = Clock::get()?;
+        WhitelistSnapshot::check_slot(slot, clock.slot)?;
+        if ctx.accounts.white_list.white_list.len() > WhitelistSnapshot::MAX_ACCOUNTS {
+            return err!(TransferError::SnapshotTooLarge);
+        }
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.bump = ctx.bumps.snapshot;
+        snapshot.slot = slot;
+        snapshot.recorded_slot = clock.slot;
+        snapshot.recorded_at = clock.unix_timestamp;
+        snapshot.accounts = ctx.accounts.white_list.white_list.iter().map(|entry| entry.account).collect();
+        msg!("White list snapshot at slot {0}, {1} account(s)", slot, snapshot.accounts.len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đóng một ảnh chụp whitelist
+     *
+     * Mục đích: Thu hồi rent của các ảnh chụp cũ không còn cần lưu trữ trên chain
+     */
+    pub fn close_snapshot(ctx: Context<CloseSnapshot>, slot: u64) -> Result<()> {
+        // Chỉ authority mới có thể đóng ảnh chụp
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        msg!("White list snapshot at slot {0} closed", slot);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đọc ảnh chụp whitelist theo trang
+     *
+     * Trả về các địa chỉ accounts[offset..offset + limit] của ảnh chụp tại slot qua return data
+     * (gọi bằng simulateTransaction), offset vượt quá cuối trả về danh sách rỗng
+     * Lưu ý: limit lớn hơn MAX_RETURN_DATA_KEYS được giảm xuống MAX_RETURN_DATA_KEYS
+     */
+    pub fn get_snapshot(ctx: Context<GetSnapshot>, _slot: u64, offset: u32, limit: u32) -> Result<Vec<Pubkey>> {
+        Ok(ctx.accounts.snapshot.page(offset, limit))
+    }
+
+    /*
+     * Hàm đề xuất chuyển quyền authority (bước 1)
+     *
+     * Mục đích: Cho phép đổi authority (ví dụ sang multisig) khi cần
+     * Quyền chỉ được chuyển khi new_authority gọi accept_authority,
+     * tránh việc chuyển nhầm sang một địa chỉ gõ sai
+     * Gọi lại hàm này sẽ thay thế đề xuất trước đó
+     * verify_owner: Nếu có, account new_authority_account (phải là new_authority)
+     * phải thuộc sở hữu của program này, ví dụ PDA của một multisig kiểu Squads
+     */
+    pub fn transfer_authority(
+        ctx: Context<TransferAuthority>,
+        new_authority: Pubkey,
+        verify_owner: Option<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority hiện tại mới có thể đề xuất chuyển quyền
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        if let Some(verify_owner) = verify_owner {
+            match &ctx.accounts.new_authority_account {
+                Some(account) => check_authority_target(account, &new_authority, &verify_owner)?,
+                None => {
+                    msg!("new_authority_account is required to verify the owner");
+                    return err!(TransferError::InvalidAuthorityTarget);
+                }
+            }
+        }
+
+        ctx.accounts.white_list.pending_authority = Some(new_authority);
+        msg!("Authority transfer proposed to {0}", new_authority.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm chấp nhận quyền authority (bước 2)
+     *
+     * Mục đích: authority mới ký để xác nhận, sau đó trở thành authority của whitelist
+     */
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let white_list = &mut ctx.accounts.white_list;
+        if white_list.pending_authority != Some(ctx.accounts.signer.key()) {
+            return err!(TransferError::NotPendingAuthority);
+        }
+
+        white_list.authority = ctx.accounts.signer.key();
+        white_list.pending_authority = None;
+        // authority mới không còn là PDA đã lưu trước đó
+        white_list.authority_pda = None;
+        msg!("Authority transferred to {0}", white_list.authority.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt authority là PDA của một program quản trị
+     *
+     * Mục đích: Chuyển quyền cho PDA của program quản trị (ví dụ DAO) với địa chỉ được
+     * kiểm chứng bằng cách suy ra từ program_id và seeds, thay vì tin một pubkey bất kỳ
+     * seeds không gồm bump, bump được tìm bằng find_program_address
+     * Thông tin suy ra được lưu trong authority_pda để kiểm tra lại khi cần
+     * Trả về InvalidAuthorityTarget nếu PDA suy ra khác account authority_pda được truyền vào
+     */
+    pub fn set_authority_pda(
+        ctx: Context<SetAuthorityPda>,
+        program_id: Pubkey,
+        seeds: Vec<Vec<u8>>
+    ) -> Result<()> {
+        // Chỉ authority hiện tại mới có thể chuyển quyền
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let (address, authority_pda) = AuthorityPda::derive(program_id, seeds)?;
+        if address != ctx.accounts.authority_pda.key() {
+            msg!("Derived authority {0} does not match {1}", address.to_string(), ctx.accounts.authority_pda.key().to_string());
+            return err!(TransferError::InvalidAuthorityTarget);
+        }
+
+        ctx.accounts.white_list.assign_authority_pda(address, authority_pda);
+        msg!("Authority set to PDA {0} of program {1}", address.to_string(), program_id.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt địa chỉ quản lý ExtraAccountMetaList
+     *
+     * Mục đích: Ghi rõ ai được gọi update_extra_account_meta_list (và trả rent khi
+     * account cần tăng kích thước), để khóa triển khai ban đầu không còn vai trò đặc biệt
+     * Đặt None để authority của whitelist quản lý
+     */
+    pub fn update_meta_list_authority(
+        ctx: Context<UpdateMetaListAuthority>,
+        meta_list_authority: Option<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi địa chỉ quản lý
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.meta_list_authority = meta_list_authority;
+        msg!("Meta list authority: {0:?}", meta_list_authority);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt program được phép quản lý whitelist qua CPI
+     *
+     * Mục đích: Cho phép whitelist được điều khiển bởi một program quản trị
+     * (ví dụ DAO thực thi kết quả bỏ phiếu) mà không cần authority ký
+     * Program này có thể thêm/xóa địa chỉ nhưng không thể thay đổi cấu hình khác
+     * Khi gọi qua CPI, program truyền PDA seeds ["program_authority", mint] của nó
+     * làm signer và ký bằng invoke_signed (xem program_authority_signer)
+     * Đặt None để tắt
+     */
+    pub fn set_program_authority(
+        ctx: Context<SetProgramAuthority>,
+        program_authority: Option<Pubkey>
+    ) -> Result<()> {
+        // Chỉ authority mới có thể ủy quyền cho program
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.program_authority = program_authority;
+        msg!("Program authority: {0:?}", program_authority);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm thêm admin
+     *
+     * Mục đích: Cho phép nhiều người cùng quản lý whitelist (thêm/xóa địa chỉ)
+     * mà không phải chia sẻ khóa của authority
+     */
+    pub fn add_admin(ctx: Context<ManageAdmins>, admin: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể quản lý danh sách admin
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        if white_list.admins.contains(&admin) {
+            return err!(TransferError::AlreadyAdmin);
+        }
+        if white_list.admins.len() >= WhiteList::MAX_ADMINS {
+            return err!(TransferError::TooManyAdmins);
+        }
+        white_list.admins.push(admin);
+        msg!("Admin added: {0}", admin.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa admin
+     *
+     * Mục đích: Thu hồi quyền quản lý whitelist của một admin
+     * Khi authority là PDA của program quản trị (set_authority_pda), admin cuối cùng
+     * không được xóa vì PDA chỉ ký được qua CPI của program đó
+     */
+    pub fn remove_admin(ctx: Context<ManageAdmins>, admin: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể quản lý danh sách admin
+        // Không được xóa authority hay admin cuối cùng khi authority là PDA
+        ctx.accounts.white_list.remove_admin(&ctx.accounts.signer.key(), &admin)?;
+        msg!("Admin removed: {0}", admin.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm thêm program được phép
+     *
+     * Mục đích: Cho phép mọi vault thuộc sở hữu của program (ví dụ AMM, giao thức cho vay)
+     * nhận token mà không cần liệt kê trước từng địa chỉ
+     */
+    pub fn add_allowed_owner_program(ctx: Context<ManageOwnerPrograms>, program: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể quản lý danh sách program
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        if white_list.allowed_owner_programs.contains(&program) {
+            return err!(TransferError::OwnerProgramAlreadyAllowed);
+        }
+        if white_list.allowed_owner_programs.len() >= WhiteList::MAX_OWNER_PROGRAMS {
+            return err!(TransferError::TooManyOwnerPrograms);
+        }
+        white_list.allowed_owner_programs.push(program);
+        msg!("Owner program allowed: {0}", program.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm xóa program được phép
+     *
+     * Các vault đã đăng ký của program này không còn được nhận token
+     */
+    pub fn remove_allowed_owner_program(ctx: Context<ManageOwnerPrograms>, program: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể quản lý danh sách program
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        match white_list.allowed_owner_programs.iter().position(|key| *key == program) {
+            Some(index) => {
+                white_list.allowed_owner_programs.remove(index);
+                msg!("Owner program removed: {0}", program.to_string());
+                Ok(())
+            },
+            None => err!(TransferError::OwnerProgramNotAllowed),
+        }
+    }
+
+    /*
+     * Hàm đăng ký một vault thuộc sở hữu của program được phép
+     *
+     * Mục đích: transfer_hook không đọc được account của chủ sở hữu account đích,
+     * nên program sở hữu vault được kiểm tra tại đây và lưu vào account ProgramVault
+     * Bất kỳ ai cũng có thể đăng ký, vì kết quả chỉ phụ thuộc vào dữ liệu on-chain
+     */
+    pub fn register_program_vault(ctx: Context<RegisterProgramVault>) -> Result<()> {
+        let program = *ctx.accounts.vault.owner;
+        if !ctx.accounts.white_list.allowed_owner_programs.contains(&program) {
+            msg!("Vault is owned by {0}", program.to_string());
+            return err!(TransferError::OwnerProgramNotAllowed);
+        }
+
+        let program_vault = &mut ctx.accounts.program_vault;
+        program_vault.owner = ctx.accounts.vault.key();
+        program_vault.program = program;
+        msg!("Vault {0} registered for program {1}", program_vault.owner.to_string(), program.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt tạm dừng toàn bộ giao dịch chuyển token
+     *
+     * Mục đích: Công tắc khẩn cấp để chặn mọi giao dịch ngay lập tức
+     * (ví dụ khi phát hiện lỗ hổng) mà không cần xóa từng địa chỉ khỏi whitelist
+     */
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        // Chỉ authority mới có thể tạm dừng/tiếp tục
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.paused = paused;
+        msg!("Transfers paused: {0}", paused);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt địa chỉ gửi được bỏ qua kiểm tra whitelist của phía nhận
+     *
+     * Mục đích: Cho phép treasury/mint authority phân phối token ban đầu
+     * đến bất kỳ ví nào mà không cần thêm trước từng người nhận
+     * Các chính sách khác (tạm dừng, giới hạn số lượng...) vẫn được áp dụng
+     * Truyền None để tắt
+     */
+    pub fn set_bypass_sender(ctx: Context<SetBypassSender>, bypass_sender: Option<Pubkey>) -> Result<()> {
+        // Chỉ authority mới có thể đặt bypass_sender
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.bypass_sender = bypass_sender;
+        msg!("Bypass sender: {0:?}", bypass_sender);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt chế độ so khớp phía nhận
+     *
+     * Mục đích: Cho phép whitelist chứa địa chỉ ví (Owner) hoặc địa chỉ
+     * account token (TokenAccount) tùy theo cách tích hợp
+     * Lưu ý: Các địa chỉ hiện có không được chuyển đổi khi đổi chế độ
+     */
+    pub fn set_match_mode(ctx: Context<SetMatchMode>, match_mode: MatchMode) -> Result<()> {
+        // Chỉ authority mới có thể đổi chế độ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.match_mode = match_mode;
+        msg!("Match mode: {0:?}", match_mode);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt yêu cầu account token đích đã tồn tại trước giao dịch
+     *
+     * Mục đích: Kiểm soát nhẹ hơn whitelist đầy đủ, ngăn việc gửi token tràn lan
+     * đến các account mới tạo. Account token đích có trong whitelist vẫn được nhận
+     */
+    pub fn set_require_existing_destination(
+        ctx: Context<SetRequireExistingDestination>,
+        require_existing_destination: bool
+    ) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.require_existing_destination = require_existing_destination;
+        msg!("Require existing destination: {0}", require_existing_destination);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt chống làm rỗng whitelist
+     *
+     * Mục đích: Whitelist rỗng chặn mọi giao dịch, khi bật cờ này các thao tác xóa
+     * (xóa một, xóa nhiều, xóa theo vị trí, xóa toàn bộ, thay thế) không được để whitelist rỗng
+     */
+    pub fn set_prevent_empty(ctx: Context<SetPreventEmpty>, prevent_empty: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.prevent_empty = prevent_empty;
+        msg!("Prevent empty: {0}", prevent_empty);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt miễn kiểm tra whitelist cho giao dịch 0 token
+     *
+     * Mục đích: Cho phép operator quyết định rõ ràng chính sách cho giao dịch 0 token
+     * Khi bật, transfer_hook chỉ kiểm tra cờ transferring và trạng thái tạm dừng
+     */
+    pub fn set_allow_zero_amount(ctx: Context<SetAllowZeroAmount>, allow_zero_amount: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.allow_zero_amount = allow_zero_amount;
+        msg!("Allow zero amount: {0}", allow_zero_amount);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt chế độ chỉ chuyển giữa các account của cùng một ví
+     *
+     * Mục đích: Hỗ trợ mô hình phân phối tự lưu ký: người nắm giữ chỉ được chuyển token
+     * giữa các account của chính mình mà không cần thêm từng account vào whitelist
+     * Khi bật, transfer_hook không kiểm tra thành viên whitelist (kể cả tạm ngưng,
+     * hết hạn, activation_delay) mà trả về CrossOwnerTransferBlocked nếu hai account
+     * thuộc hai ví khác nhau
+     * Giới hạn số lượng, lịch giải phóng của escrow, giới hạn tần suất và quota
+     * vẫn được áp dụng cho giao dịch trong cùng một ví
+     */
+    pub fn set_same_owner_only(ctx: Context<SetSameOwnerOnly>, same_owner_only: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.same_owner_only = same_owner_only;
+        msg!("Same owner only: {0}", same_owner_only);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt miễn kiểm tra whitelist khi đốt token
+     *
+     * Mục đích: Cho phép người nắm giữ đốt token (chuyển đến incinerator) kể cả khi
+     * whitelist chặt chẽ, hoặc tắt nếu mint không cho phép đốt
+     */
+    pub fn set_allow_burn(ctx: Context<SetAllowBurn>, allow_burn: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.allow_burn = allow_burn;
+        msg!("Allow burn: {0}", allow_burn);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt sự kiện TransferObserved cho mỗi giao dịch thành công
+     *
+     * Mục đích: Cung cấp nguồn dữ liệu kiểm toán cho indexer đọc từ log giao dịch
+     */
+    pub fn set_verbose_logging(ctx: Context<SetVerboseLogging>, verbose_logging: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.verbose_logging = verbose_logging;
+        msg!("Verbose logging: {0}", verbose_logging);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm bật/tắt kiểm tra phía gửi
+     *
+     * Mục đích: Với token bị quản lý chặt, cả hai phía đều phải được kiểm soát
+     * Khi bật, owner hoặc account token nguồn phải có trong whitelist
+     */
+    pub fn set_check_source(ctx: Context<SetCheckSource>, check_source: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.check_source = check_source;
+        msg!("Source check enabled: {0}", check_source);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cho phép/chặn tự chuyển token
+     *
+     * Mục đích: Chặn các giao dịch chuyển từ một account token về chính nó
+     * (ví dụ wash-trading) với token bị quản lý chặt
+     * Mặc định cho phép, giống hành vi trước đây
+     */
+    pub fn set_allow_self_transfer(ctx: Context<SetAllowSelfTransfer>, allow_self_transfer: bool) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.block_self_transfer = !allow_self_transfer;
+        msg!("Self-transfer allowed: {0}", allow_self_transfer);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đóng account whitelist và thu hồi rent
+     *
+     * Mục đích: Thu hồi lamports bị khóa trong PDA whitelist khi dự án kết thúc
+     * QUAN TRỌNG: Đây là trạng thái cuối cùng. Sau khi đóng, transfer_hook
+     * sẽ thất bại với MỌI giao dịch chuyển token vì PDA whitelist không còn tồn tại
+     */
+    pub fn close_whitelist(ctx: Context<CloseWhiteList>) -> Result<()> {
+        // Chỉ authority mới có thể đóng whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        msg!("White list closed, rent returned to {0}", ctx.accounts.signer.key().to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đổi chế độ danh sách
+     *
+     * Mục đích: Cho phép token được chuyển tự do và chỉ chặn một số địa chỉ
+     * (DenyList), thay vì chỉ cho phép các địa chỉ trong danh sách (AllowList)
+     */
+    pub fn set_mode(ctx: Context<SetMode>, mode: WhitelistMode) -> Result<()> {
+        // Chỉ authority mới có thể đổi chế độ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.mode = mode;
+        msg!("White list mode: {0:?}", mode);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt giới hạn số lượng cho mỗi giao dịch chuyển token
+     *
+     * Mục đích: Giới hạn số lượng token có thể chuyển trong một giao dịch
+     * Đặt None để bỏ giới hạn
+     */
+    pub fn set_max_transfer_amount(
+        ctx: Context<SetMaxTransferAmount>,
+        max_transfer_amount: Option<u64>
+    ) -> Result<()> {
+        // Chỉ authority mới có thể đặt giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.max_transfer_amount = max_transfer_amount;
+        msg!("Max transfer amount: {0:?}", max_transfer_amount);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt giới hạn số lượng mỗi giao dịch theo đơn vị token nguyên
+     *
+     * Mục đích: Operator nhập giới hạn theo đơn vị hiển thị (ví dụ 100 token)
+     * thay vì đơn vị cơ sở phụ thuộc vào decimals của mint
+     * Giá trị được nhân với 10^decimals và lưu vào max_transfer_amount,
+     * decimals của mint không thay đổi nên transfer_hook so sánh trực tiếp
+     */
+    pub fn set_max_transfer_whole_tokens(ctx: Context<SetMaxTransferAmount>, whole: u64) -> Result<()> {
+        // Chỉ authority mới có thể đặt giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let max_transfer_amount = whole_tokens_to_base_units(whole, ctx.accounts.mint.decimals)?;
+        ctx.accounts.white_list.max_transfer_amount = Some(max_transfer_amount);
+        msg!("Max transfer amount: {0} tokens ({1} base units)", whole, max_transfer_amount);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt số lượng tối thiểu cho mỗi giao dịch chuyển token
+     *
+     * Mục đích: Chặn các giao dịch rất nhỏ dùng để spam hoặc đầu độc địa chỉ
+     * (address poisoning). Kết hợp với max_transfer_amount để tạo khoảng cho phép
+     * Đặt 0 để tắt
+     */
+    pub fn set_min_transfer_amount(ctx: Context<SetMinTransferAmount>, min_transfer_amount: u64) -> Result<()> {
+        // Chỉ authority mới có thể đặt giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.min_transfer_amount = min_transfer_amount;
+        msg!("Min transfer amount: {0}", min_transfer_amount);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt cấp tin cậy của một địa chỉ trong whitelist
+     *
+     * Mục đích: Phân biệt địa chỉ "tin cậy" và địa chỉ "thử thách" để áp dụng
+     * giới hạn số lượng khác nhau (xem set_tier_limits)
+     * tier: 0 hoặc 1
+     */
+    pub fn set_entry_tier(ctx: Context<SetEntryTier>, account: Pubkey, tier: u8) -> Result<()> {
+        // Chỉ authority mới có thể đặt cấp
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        if tier >= WhiteList::TIER_COUNT {
+            return err!(TransferError::InvalidTier);
+        }
+
+        match ctx.accounts.white_list.position(&account) {
+            Some(index) => {
+                ctx.accounts.white_list.white_list[index].tier = tier;
+                msg!("Account {0} set to tier {1}", account.to_string(), tier);
+                Ok(())
+            }
+            None => {
+                msg!("Account not found in whitelist: {0}", account.to_string());
+                err!(TransferError::AccountNotFound)
+            }
+        }
+    }
+
+    /*
+     * Hàm tạm ngưng quyền nhận token của một địa chỉ
+     *
+     * Mục đích: Tạm giữ một địa chỉ (ví dụ trong thời gian điều tra) mà không xóa khỏi
+     * whitelist, nên không mất cấp, thời hạn và thời điểm được thêm
+     * transfer_hook trả về AddressSuspended cho địa chỉ bị tạm ngưng
+     */
+    pub fn suspend_address(ctx: Context<SuspendAddress>, account: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể tạm ngưng địa chỉ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.set_suspended(&account, true)?;
+        msg!("Account suspended: {0}", account.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm khôi phục quyền nhận token của một địa chỉ đã bị tạm ngưng
+     */
+    pub fn unsuspend_address(ctx: Context<SuspendAddress>, account: Pubkey) -> Result<()> {
+        // Chỉ authority mới có thể khôi phục địa chỉ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.set_suspended(&account, false)?;
+        msg!("Account unsuspended: {0}", account.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt giới hạn số lượng mỗi giao dịch theo cấp của account đích
+     *
+     * Mục đích: Cho phép địa chỉ tin cậy nhận số lượng bất kỳ trong khi
+     * địa chỉ thử thách chỉ được nhận số lượng nhỏ
+     * Đặt 0 để tắt giới hạn của cấp tương ứng
+     */
+    pub fn set_tier_limits(ctx: Context<SetTierLimits>, tier0_max: u64, tier1_max: u64) -> Result<()> {
+        // Chỉ authority mới có thể đặt giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.tier0_max = tier0_max;
+        ctx.accounts.white_list.tier1_max = tier1_max;
+        msg!("Tier limits: {0} / {1}", tier0_max, tier1_max);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt thời gian chờ giữa hai lần thay đổi (thêm/xóa) của cùng một địa chỉ
+     *
+     * Mục đích: Ngăn việc xóa rồi thêm lại một địa chỉ liên tục để đặt lại
+     * activation_delay hoặc lách các kiểm tra dựa trên thời gian
+     * Đặt 0 để tắt
+     */
+    pub fn set_cooldown_slots(ctx: Context<SetCooldownSlots>, cooldown_slots: u64) -> Result<()> {
+        // Chỉ authority mới có thể đặt thời gian chờ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.cooldown_slots = cooldown_slots;
+        msg!("Cooldown: {0} slot(s)", cooldown_slots);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt thời gian chờ kích hoạt cho địa chỉ mới
+     *
+     * Mục đích: Yêu cầu một khoảng thời gian chờ (tính bằng giây) sau khi
+     * một địa chỉ được thêm vào whitelist trước khi nó có thể nhận token
+     * Đặt 0 để tắt, giá trị âm trả về InvalidActivationDelay
+     */
+    pub fn set_activation_delay(ctx: Context<SetActivationDelay>, activation_delay: i64) -> Result<()> {
+        // Chỉ authority mới có thể đặt thời gian chờ
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        if activation_delay < 0 {
+            return err!(TransferError::InvalidActivationDelay);
+        }
+
+        ctx.accounts.white_list.activation_delay = activation_delay;
+        msg!("Activation delay: {0} seconds", activation_delay);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt tên hiển thị cho whitelist
+     *
+     * Mục đích: Giúp phân biệt các whitelist của nhiều mint trên trang quản trị
+     * name: Chuỗi UTF-8 tối đa 32 bytes, đệm bằng byte 0
+     */
+    pub fn set_name(ctx: Context<SetName>, name: [u8; 32]) -> Result<()> {
+        // Chỉ authority mới có thể đặt tên
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.name = name;
+        let len = name.iter().position(|byte| *byte == 0).unwrap_or(name.len());
+        msg!("White list name: {0}", String::from_utf8_lossy(&name[..len]));
+
+        Ok(())
+    }
+
+    /*
+     * Hàm nâng cấp layout của account whitelist
+     *
+     * Mục đích: Khi WhiteList có thêm trường mới, account cũ có thể không còn
+     * đủ chỗ cho các trường đó. Hàm này tăng kích thước account (nếu cần) để
+     * chứa các địa chỉ hiện có cùng toàn bộ trường mới, phần dữ liệu thêm vào
+     * là các byte 0 tương ứng với giá trị mặc định
+     * Gọi lại nhiều lần là an toàn (không làm gì nếu đã đủ kích thước)
+     */
+    pub fn upgrade_whitelist_layout(ctx: Context<UpgradeWhiteListLayout>) -> Result<()> {
+        let white_list_info = ctx.accounts.white_list.to_account_info();
+        let required_len = {
+            let data = white_list_info.try_borrow_data()?;
+            // Chỉ đọc phần đầu cố định: discriminator, version, authority, độ dài vector
+            // Account phiên bản cũ phải được chuyển đổi bằng migrate trước
+            if WhiteList::stored_version(&data, &ctx.accounts.signer.key())? != WhiteList::VERSION {
+                return err!(TransferError::MigrationRequired);
+            }
+            let offset = WhiteList::WHITE_LIST_LEN_OFFSET;
+            let entries = data[offset..offset + 4]
+                .try_into()
+                .map(u32::from_le_bytes)
+                .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            WhiteList::space_for(entries as usize)
+        };
+
+        if white_list_info.data_len() < required_len {
+            grow_program_account(
+                &white_list_info,
+                &ctx.accounts.signer.to_account_info(),
+                &ctx.accounts.system_program,
+                required_len
+            )?;
+        }
+
+        // Ghi bump cho account được tạo trước khi bump được lưu trên WhiteList
+        {
+            let mut data = white_list_info.try_borrow_mut_data()?;
+            let mut white_list = WhiteList::try_deserialize(&mut &data[..])?;
+            white_list.white_list_bump = ctx.bumps.white_list;
+            white_list.meta_list_bump = WhiteList::find_meta_list_bump(&ctx.accounts.mint.key());
+            // Sắp xếp lại whitelist được tạo trước khi tra cứu bằng tìm kiếm nhị phân
+            white_list.sort_entries();
+            white_list.try_serialize(&mut &mut data[..])?;
+        }
+        msg!("White list layout upgraded, account size {0}", white_list_info.data_len());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm chuyển đổi account whitelist phiên bản cũ sang layout hiện tại
+     *
+     * Mục đích: Account được tạo trước khi có trường version (phiên bản 0) lưu authority
+     * ngay sau discriminator, account phiên bản 1 chưa có chỗ cho các trường phí, nên không
+     * đọc được theo layout mới, mọi instruction khác trả về MigrationRequired cho đến khi
+     * account được chuyển đổi
+     * Hàm này tăng kích thước account (authority trả thêm rent), dời dữ liệu để chèn version
+     * (phiên bản 0) và điền giá trị mặc định cho các trường mới
+     * Gọi lại là an toàn (không làm gì nếu account đã ở phiên bản hiện tại)
+     */
+    pub fn migrate(ctx: Context<MigrateWhiteListVersion>) -> Result<()> {
+        let white_list_info = ctx.accounts.white_list.to_account_info();
+        let old_len = white_list_info.data_len();
+        let (version, entries) = {
+            let data = white_list_info.try_borrow_data()?;
+            // Chỉ authority mới có thể chuyển đổi whitelist
+            let version = WhiteList::stored_version(&data, &ctx.accounts.signer.key())?;
+            // Account phiên bản 0 chưa có byte version nên độ dài vector nằm sớm hơn 1 byte
+            let offset = WhiteList::WHITE_LIST_LEN_OFFSET - usize::from(version == 0);
+            let entries = data[offset..offset + 4]
+                .try_into()
+                .map(u32::from_le_bytes)
+                .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            (version, entries)
+        };
+        match version {
+            WhiteList::VERSION => {
+                msg!("White list is already at version {0}", version);
+                return Ok(());
+            }
+            0 | 1 => {}
+            _ => return err!(ErrorCode::AccountDidNotDeserialize),
+        }
+
+        // Phiên bản 0 cần thêm ít nhất 1 byte cho version, không bao giờ thu nhỏ account
+        let required_len = WhiteList::space_for(entries as usize).max(old_len + usize::from(version == 0));
+        grow_program_account(
+            &white_list_info,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program,
+            required_len
+        )?;
+
+        {
+            let mut data = white_list_info.try_borrow_mut_data()?;
+            // Kiểm tra dữ liệu đọc được theo layout mới, đồng thời ghi lại bump
+            let mut white_list = WhiteList::upgrade_layout(&mut data, version, old_len)?;
+            white_list.white_list_bump = ctx.bumps.white_list;
+            white_list.meta_list_bump = WhiteList::find_meta_list_bump(&ctx.accounts.mint.key());
+            white_list.try_serialize(&mut &mut data[..])?;
+        }
+        msg!("White list migrated from version {0} to {1}", version, WhiteList::VERSION);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm sắp xếp lại whitelist theo địa chỉ và bỏ các địa chỉ bị lặp
+     *
+     * Mục đích: Whitelist được tạo trước khi tra cứu bằng tìm kiếm nhị phân có thể
+     * chưa được sắp xếp, khi đó tra cứu có thể bỏ sót địa chỉ. Chạy một lần sau khi
+     * nâng cấp program để account tương thích với tra cứu nhanh
+     * Gọi lại là an toàn (không thay đổi gì nếu đã được sắp xếp)
+     */
+    pub fn sort_whitelist(ctx: Context<SortWhiteList>) -> Result<()> {
+        // Chỉ authority mới có thể sắp xếp lại whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let removed = ctx.accounts.white_list.sort_entries();
+        msg!(
+            "White list sorted, {0} duplicates removed, length: {1}",
+            removed,
+            ctx.accounts.white_list.white_list.len()
+        );
+
+        Ok(())
+    }
+
+    /*
+     * Hàm di chuyển whitelist toàn cục cũ sang whitelist theo từng mint
+     *
+     * Mục đích: Các bản triển khai cũ dùng một PDA whitelist chung (seeds "white_list"),
+     * hàm này sao chép authority và các địa chỉ sang PDA mới của mint
+     * Gọi lại là an toàn: các địa chỉ đã được sao chép sẽ được bỏ qua
+     * close_legacy: Đóng account cũ và trả rent cho authority sau khi sao chép
+     * Lưu ý: Nếu cần di chuyển cho nhiều mint, chỉ đóng account cũ ở lần cuối cùng
+     */
+    pub fn migrate_whitelist(ctx: Context<MigrateWhiteList>, close_legacy: bool) -> Result<()> {
+        let legacy = LegacyWhiteList::try_from_account(&ctx.accounts.legacy_white_list)?;
+        // Chỉ authority của whitelist cũ mới có thể di chuyển
+        if legacy.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list = &mut ctx.accounts.white_list;
+        // Whitelist mới vừa được tạo sẽ nhận authority của whitelist cũ,
+        // whitelist đã tồn tại phải thuộc cùng một authority
+        if white_list.authority == Pubkey::default() {
+            white_list.version = WhiteList::VERSION;
+            white_list.authority = legacy.authority;
+            white_list.allow_burn = true;
+        } else if white_list.authority != legacy.authority {
+            return err!(TransferError::Unauthorized);
+        } else if white_list.version != WhiteList::VERSION {
+            return err!(TransferError::MigrationRequired);
+        }
+        white_list.white_list_bump = ctx.bumps.white_list;
+        white_list.meta_list_bump = WhiteList::find_meta_list_bump(&ctx.accounts.mint.key());
+
+        let capacity = white_list.capacity(white_list.to_account_info().data_len());
+        let clock = Clock::get()?;
+        let added_at = clock.unix_timestamp;
+        let mut migrated = 0;
+        for account in legacy.white_list {
+            // Bỏ qua địa chỉ đã được sao chép ở lần gọi trước
+            if white_list.contains(&account) {
+                continue;
+            }
+            white_list.push_entry(WhiteListEntry { account, added_at, expires_at: None, tier: 0, changed_slot: clock.slot, suspended: false }, capacity)?;
+            migrated += 1;
+            emit!(WhitelistAdded {
+                account,
+                authority: legacy.authority,
+                length: white_list.white_list.len() as u32,
+            });
+        }
+        msg!("Migrated {0} account(s) to mint white list", migrated);
+
+        if close_legacy {
+            close_program_account(
+                &ctx.accounts.legacy_white_list.to_account_info(),
+                &ctx.accounts.signer.to_account_info()
+            )?;
+            msg!("Legacy white list closed");
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Hàm thay đổi kích thước account whitelist
+     *
+     * Mục đích: Cho phép whitelist chứa nhiều hơn max_entries đã chọn khi khởi tạo
+     * Kích thước mới = WhiteList::space_for(new_capacity)
+     * max_entries được đặt bằng new_capacity, trừ khi bằng 0 (chỉ giới hạn theo kích thước)
+     * Không cho phép giảm xuống dưới số địa chỉ hiện có
+     * Khi tăng, authority nạp thêm lamports đến mức miễn rent của kích thước mới,
+     * trả về InsufficientRentTopUp nếu không đủ; khi giảm, rent dư được trả lại authority
+     */
+    pub fn resize_whitelist(ctx: Context<ResizeWhiteList>, new_capacity: u32) -> Result<()> {
+        // Chỉ authority mới có thể thay đổi kích thước whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        // Không được giảm dung lượng xuống dưới số địa chỉ hiện có
+        let current_len = ctx.accounts.white_list.white_list.len();
+        if (new_capacity as usize) < current_len {
+            msg!("Cannot resize to {0} entries, white list has {1}", new_capacity, current_len);
+            return err!(TransferError::CapacityTooSmall);
+        }
+
+        let white_list_info = ctx.accounts.white_list.to_account_info();
+        let signer_info = ctx.accounts.signer.to_account_info();
+        let old_len = white_list_info.data_len();
+        let new_len = WhiteList::space_for(new_capacity as usize);
+        if new_len > old_len {
+            if new_len - old_len > MAX_PERMITTED_DATA_INCREASE {
+                return err!(TransferError::ReallocFailed);
+            }
+            grow_program_account(&white_list_info, &signer_info, &ctx.accounts.system_program, new_len)?;
+        } else if new_len < old_len {
+            white_list_info.resize(new_len)?;
+            // Trả lại phần rent dư cho authority, account vẫn giữ đủ mức miễn rent
+            let surplus = rent_surplus(&Rent::get()?, white_list_info.lamports(), new_len);
+            **signer_info.try_borrow_mut_lamports()? = signer_info
+                .lamports()
+                .checked_add(surplus)
+                .ok_or(TransferError::AmountOverflow)?;
+            **white_list_info.try_borrow_mut_lamports()? -= surplus;
+        }
+        // Cập nhật giới hạn số địa chỉ theo dung lượng mới (0 = chỉ giới hạn theo kích thước),
+        // giống như grow_whitelist
+        if ctx.accounts.white_list.max_entries != 0 {
+            ctx.accounts.white_list.max_entries = new_capacity.min(u16::MAX as u32) as u16;
+        }
+
+        msg!("White list resized to hold {0} entries", new_capacity);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm thu hồi rent dư của account whitelist
+     *
+     * Mục đích: Sau khi giảm kích thước (resize_whitelist) hoặc khi account được nạp thừa,
+     * chuyển phần lamports vượt quá mức miễn rent của kích thước hiện tại về authority
+     * Account luôn giữ đủ mức miễn rent, không có gì để thu hồi thì không thay đổi gì
+     */
+    pub fn reclaim_surplus_rent(ctx: Context<ReclaimSurplus>) -> Result<()> {
+        // Chỉ authority mới có thể thu hồi rent
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list_info = ctx.accounts.white_list.to_account_info();
+        let surplus = rent_surplus(&Rent::get()?, white_list_info.lamports(), white_list_info.data_len());
+        if surplus > 0 {
+            // Program sở hữu account whitelist nên có thể trừ lamports trực tiếp
+            let signer_info = ctx.accounts.signer.to_account_info();
+            **signer_info.try_borrow_mut_lamports()? = signer_info
+                .lamports()
+                .checked_add(surplus)
+                .ok_or(TransferError::AmountOverflow)?;
+            **white_list_info.try_borrow_mut_lamports()? -= surplus;
+        }
+        msg!("Reclaimed {0} lamports of surplus rent", surplus);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tăng dung lượng whitelist thêm additional_entries địa chỉ
+     *
+     * Mục đích: Tăng dần dung lượng một cách an toàn: phần tăng thêm được khởi tạo
+     * bằng 0, các địa chỉ hiện có được giữ nguyên và được kiểm tra lại sau khi tăng
+     * authority trả thêm rent cho phần tăng thêm
+     * Trả về ReallocFailed nếu vượt quá giới hạn realloc mỗi instruction (10240 bytes),
+     * khi đó cần chia thành nhiều giao dịch
+     */
+    pub fn grow_whitelist(ctx: Context<GrowWhiteList>, additional_entries: u16) -> Result<()> {
+        // Chỉ authority mới có thể tăng dung lượng whitelist
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let white_list_info = ctx.accounts.white_list.to_account_info();
+        let new_len = WhiteList::grown_len(white_list_info.data_len(), additional_entries)?;
+        grow_program_account(
+            &white_list_info,
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program,
+            new_len
+        )?;
+
+        // Kiểm tra dữ liệu hiện có vẫn đọc được sau khi tăng kích thước
+        let entries = {
+            let data = white_list_info.try_borrow_data()?;
+            WhiteList::try_deserialize(&mut &data[..])?.white_list.len()
+        };
+        if entries != ctx.accounts.white_list.white_list.len() {
+            return err!(TransferError::ReallocFailed);
+        }
+
+        // Cập nhật giới hạn số địa chỉ theo dung lượng mới (0 = chỉ giới hạn theo kích thước)
+        let capacity = WhiteList::capacity_for(new_len);
+        if ctx.accounts.white_list.max_entries != 0 {
+            ctx.accounts.white_list.max_entries = capacity.min(u16::MAX as usize) as u16;
+        }
+        msg!("White list grown to hold {0} entries, account size {1}", capacity, new_len);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cấu hình token thành viên
+     *
+     * Mục đích: Chỉ cho phép chuyển token đến các ví nắm giữ ít nhất
+     * required_balance token của required_mint
+     * Đặt required_balance = 0 để tắt tính năng
+     */
+    pub fn set_membership_requirement(
+        ctx: Context<SetMembershipRequirement>,
+        required_balance: u64
+    ) -> Result<()> {
+        // Chỉ authority của whitelist mới được thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let required_mint = ctx.accounts.required_mint.to_account_info();
+        let config = &mut ctx.accounts.membership_config;
+        config.required_mint = required_mint.key();
+        // Lưu token program sở hữu mint thành viên để suy ra đúng địa chỉ ATA
+        config.required_mint_token_program = *required_mint.owner;
+        config.required_balance = required_balance;
+        msg!(
+            "Membership requirement set: {0} of {1}",
+            required_balance,
+            config.required_mint.to_string()
+        );
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt mint bắt buộc nắm giữ
+     *
+     * Mục đích: Chỉ cho phép chuyển token đến các ví đang nắm giữ token của
+     * một mint khác (ví dụ token đối tác), tạo điều kiện kết hợp giữa các token
+     * Bỏ trống account required_mint để tắt yêu cầu
+     */
+    pub fn set_required_mint(ctx: Context<SetRequiredMint>) -> Result<()> {
+        // Chỉ authority của whitelist mới được thay đổi cấu hình
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let config = &mut ctx.accounts.required_mint_config;
+        match &ctx.accounts.required_mint {
+            Some(required_mint) => {
+                let required_mint = required_mint.to_account_info();
+                config.required_mint = required_mint.key();
+                // Lưu token program sở hữu mint để suy ra đúng địa chỉ ATA
+                config.required_mint_token_program = *required_mint.owner;
+                ctx.accounts.white_list.required_mint = Some(required_mint.key());
+                msg!("Required mint: {0}", required_mint.key().to_string());
+            }
+            None => {
+                config.required_mint = Pubkey::default();
+                config.required_mint_token_program = Pubkey::default();
+                ctx.accounts.white_list.required_mint = None;
+                msg!("Required mint disabled");
+            }
+        }
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt lịch giải phóng cho một account escrow
+     *
+     * Mục đích: Biến escrow có trong whitelist thành một vesting escrow
+     * Token chuyển ra khỏi escrow chỉ được phép trong giới hạn đã giải phóng:
+     * không có gì trước start + cliff, giải phóng tuyến tính đến start + duration
+     * Lịch bắt đầu tính từ thời điểm gọi hàm, số dư hiện có của escrow
+     * được tính là đã gửi vào
+     *
+     * Tương tác với các giới hạn thời gian khác: chương trình không có tính năng
+     * holding-period riêng. Lịch giải phóng chỉ giới hạn phía gửi (token ra khỏi
+     * escrow), còn activation_delay chỉ trì hoãn việc một địa chỉ mới thêm được
+     * nhận token và cooldown_slots chỉ chặn việc thêm lại địa chỉ vừa bị xóa.
+     * Các kiểm tra này áp dụng độc lập trong evaluate_transfer, nên một lần chuyển ra
+     * khỏi escrow phải thỏa mãn tất cả. Ở chế độ same_owner_only, activation_delay
+     * được bỏ qua cùng kiểm tra thành viên còn lịch giải phóng vẫn được áp dụng
+     */
+    pub fn set_release_schedule(
+        ctx: Context<SetReleaseSchedule>,
+        account: Pubkey,
+        cliff: i64,
+        duration: i64
+    ) -> Result<()> {
+        // Chỉ authority của whitelist mới được đặt lịch giải phóng
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+        // Lịch chỉ áp dụng cho escrow có chủ sở hữu đã được thêm vào whitelist
+        let escrow_owner = ctx.accounts.escrow_token.owner;
+        if !ctx.accounts.white_list.contains(&escrow_owner) {
+            msg!("Escrow owner not found in whitelist: {0}", escrow_owner.to_string());
+            return err!(TransferError::AccountNotFound);
+        }
+        if duration <= 0 || cliff < 0 || cliff > duration {
+            return err!(TransferError::InvalidReleaseSchedule);
+        }
+
+        let schedule = &mut ctx.accounts.release_schedule;
+        schedule.escrow = account;
+        schedule.start_ts = Clock::get()?.unix_timestamp;
+        schedule.cliff = cliff;
+        schedule.duration = duration;
+        schedule.total_deposited = ctx.accounts.escrow_token.amount;
+        schedule.withdrawn = 0;
+        msg!(
+            "Release schedule set for {0}: cliff {1}s, duration {2}s",
+            account.to_string(),
+            cliff,
+            duration
+        );
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt giới hạn tổng số token một account đích được nhận
+     *
+     * Mục đích: Giới hạn tổng lượng token một account có thể nhận qua nhiều giao dịch,
+     * bổ sung cho giới hạn mỗi giao dịch (max_transfer_amount)
+     * Số token đã nhận được giữ nguyên khi thay đổi giới hạn
+     */
+    pub fn set_receiving_cap(ctx: Context<SetReceivingCap>, account: Pubkey, cap: u128) -> Result<()> {
+        // Chỉ authority của whitelist mới được đặt giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let receiving_limit = &mut ctx.accounts.receiving_limit;
+        receiving_limit.destination = account;
+        receiving_limit.cap = cap;
+        msg!(
+            "Receiving cap set for {0}: {1}, received {2}",
+            account.to_string(),
+            cap,
+            receiving_limit.received
+        );
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tạo trước account theo dõi của một account đích
+     *
+     * Mục đích: transfer_hook không được tạo account (không thể trả rent), nên account
+     * theo dõi theo account đích (recv_limit) được tạo trước để hook chỉ đọc/cập nhật
+     * Account được tạo không có giới hạn (cap = u128::MAX), authority có thể đặt
+     * giới hạn sau bằng set_receiving_cap
+     * Trả về lỗi RecordAlreadyExists nếu account đã được tạo
+     */
+    pub fn init_destination_record(ctx: Context<InitDestinationRecord>, destination: Pubkey) -> Result<()> {
+        // Chỉ authority hoặc chủ sở hữu account đích mới được tạo
+        let signer = ctx.accounts.signer.key();
+        if ctx.accounts.white_list.authority != signer && ctx.accounts.destination_token.owner != signer {
+            return err!(TransferError::Unauthorized);
+        }
+
+        let receiving_limit = &mut ctx.accounts.receiving_limit;
+        if receiving_limit.destination != Pubkey::default() {
+            msg!("Destination record already exists for {0}", destination.to_string());
+            return err!(TransferError::RecordAlreadyExists);
+        }
+        receiving_limit.destination = destination;
+        receiving_limit.cap = u128::MAX;
+        receiving_limit.received = 0;
+        msg!("Destination record created for {0}", destination.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt root của cây Merkle chứa các địa chỉ được phép
+     *
+     * Mục đích: Hỗ trợ danh sách rất lớn mà không cần lưu từng địa chỉ on-chain
+     * Chỉ có hiệu lực khi mode = MerkleRoot (đổi bằng set_mode)
+     * Đổi root sẽ vô hiệu hóa mọi bằng chứng đã gửi trước đó
+     */
+    pub fn set_merkle_root(ctx: Context<SetMerkleRoot>, merkle_root: [u8; 32]) -> Result<()> {
+        // Chỉ authority mới có thể đặt root
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.merkle_root = merkle_root;
+        msg!("Merkle root updated");
+
+        Ok(())
+    }
+
+    /*
+     * Hàm đặt program cấp attestation cho chế độ Attestation
+     *
+     * Mục đích: Cho phép nhận token dựa trên attestation (ví dụ chứng nhận KYC) do một
+     * program khác cấp thay vì danh sách được quản lý thủ công. Attestation của mỗi ví
+     * là PDA ["attestation", owner] của attestation_program
+     * expiry_offset: Vị trí của expires_at (i64) trong dữ liệu attestation, None = không hết hạn
+     * ExtraAccountMetaList được ghi lại để Token-2022 suy ra attestation theo program mới
+     * Dùng set_mode(Attestation) để bật chế độ này
+     */
+    pub fn set_attestation_program(
+        ctx: Context<SetAttestationProgram>,
+        attestation_program: Option<Pubkey>,
+        expiry_offset: Option<u16>
+    ) -> Result<()> {
+        // Chỉ authority mới có thể đặt program cấp attestation
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.attestation_program = attestation_program;
+        ctx.accounts.white_list.attestation_expiry_offset = expiry_offset;
+        rewrite_extra_account_meta_list(
+            &ctx.accounts.extra_account_meta_list.to_account_info(),
+            &ctx.accounts.signer.to_account_info(),
+            &ctx.accounts.system_program,
+            attestation_program
+        )?;
+        msg!("Attestation program: {0:?}, expiry offset: {1:?}", attestation_program, expiry_offset);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm gửi bằng chứng Merkle cho một địa chỉ
+     *
+     * Mục đích: Chứng minh owner thuộc cây Merkle có root là merkle_root hiện tại
+     * Kết quả được lưu vào account MerkleMembership để transfer_hook kiểm tra
+     * Lá của cây là hash(owner), các cặp nút được sắp xếp trước khi hash
+     */
+    pub fn submit_merkle_proof(
+        ctx: Context<SubmitMerkleProof>,
+        owner: Pubkey,
+        proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        let root = ctx.accounts.white_list.merkle_root;
+        if !verify_merkle_proof(merkle_leaf(&owner), &proof, &root) {
+            msg!("Invalid Merkle proof for {0}", owner.to_string());
+            return err!(TransferError::InvalidMerkleProof);
+        }
+
+        let membership = &mut ctx.accounts.merkle_membership;
+        membership.owner = owner;
+        membership.root = root;
+        msg!("Merkle proof verified for {0}", owner.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cấu hình giới hạn tần suất chuyển token của mỗi owner nguồn
+     *
+     * Mục đích: Chống lạm dụng bằng cách giới hạn số giao dịch mỗi owner
+     * được thực hiện trong window_seconds giây
+     * Đặt max_per_window = 0 để tắt
+     * Trả về InvalidWindow nếu window_seconds âm, hoặc bằng 0 khi giới hạn được bật
+     * (khoảng thời gian 0 giây đặt lại bộ đếm ở mỗi giao dịch, tức là tắt giới hạn)
+     * Lưu ý: Khi bật, owner phải tạo bộ đếm bằng create_rate_counter trước khi chuyển token
+     */
+    pub fn set_rate_limit(ctx: Context<SetRateLimit>, max_per_window: u32, window_seconds: i64) -> Result<()> {
+        // Chỉ authority mới có thể cấu hình giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        if window_seconds < 0 || (max_per_window > 0 && window_seconds == 0) {
+            return err!(TransferError::InvalidWindow);
+        }
+
+        ctx.accounts.white_list.max_per_window = max_per_window;
+        ctx.accounts.white_list.window_seconds = window_seconds;
+        msg!("Rate limit: {0} transfers per {1} seconds", max_per_window, window_seconds);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tạo bộ đếm giới hạn tần suất cho một owner
+     *
+     * Mục đích: transfer hook không thể trả phí tạo account, nên bộ đếm phải
+     * được tạo trước. Bất kỳ ai cũng có thể trả phí tạo cho owner
+     */
+    pub fn create_rate_counter(ctx: Context<CreateRateCounter>, owner: Pubkey) -> Result<()> {
+        let rate_counter = &mut ctx.accounts.rate_counter;
+        rate_counter.owner = owner;
+        rate_counter.window_start = Clock::get()?.unix_timestamp;
+        rate_counter.count = 0;
+        msg!("Rate counter created for {0}", owner.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cấu hình quota chuyển token mỗi epoch của mỗi owner nguồn
+     *
+     * Mục đích: Giới hạn tổng số lượng mỗi owner được chuyển trong một epoch,
+     * tổng được đặt lại khi sang epoch mới
+     * Đặt per_epoch_quota = 0 để tắt
+     * Lưu ý: Khi bật, owner phải tạo bộ đếm bằng create_epoch_quota trước khi chuyển token
+     */
+    pub fn set_epoch_quota(ctx: Context<SetEpochQuota>, per_epoch_quota: u64) -> Result<()> {
+        // Chỉ authority mới có thể cấu hình quota
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        ctx.accounts.white_list.per_epoch_quota = per_epoch_quota;
+        msg!("Per-epoch quota: {0}", per_epoch_quota);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tạo bộ đếm quota mỗi epoch cho một owner
+     *
+     * Mục đích: transfer hook không thể trả phí tạo account, nên bộ đếm phải
+     * được tạo trước. Bất kỳ ai cũng có thể trả phí tạo cho owner
+     */
+    pub fn create_epoch_quota(ctx: Context<CreateEpochQuota>, owner: Pubkey) -> Result<()> {
+        let epoch_quota = &mut ctx.accounts.epoch_quota;
+        epoch_quota.owner = owner;
+        epoch_quota.epoch = Clock::get()?.epoch;
+        epoch_quota.moved = 0;
+        msg!("Epoch quota counter created for {0}", owner.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm cấu hình phí giao thức của mint
+     *
+     * Mục đích: Mỗi giao dịch thành công ghi nhận amount * fee_basis_points / 10000
+     * vào treasury của mint, việc thu token phí được thực hiện riêng
+     * fee_basis_points = 0 tắt phí, đây cũng là giá trị của whitelist cũ nên hành vi
+     * của các mint hiện có không thay đổi
+     * Trả về FeeTooHigh nếu vượt quá MAX_FEE_BASIS_POINTS (1000 = 10%)
+     * Lưu ý: Treasury được tạo ở lần gọi đầu tiên (authority trả phí tạo account),
+     * ExtraAccountMetaList tạo trước khi có phí cần được cập nhật bằng update_extra_account_meta_list
+     */
+    pub fn set_fee_basis_points(ctx: Context<SetFee>, fee_basis_points: u16) -> Result<()> {
+        ctx.accounts.set(fee_basis_points as u32, WhiteList::FEE_DENOMINATOR)
+    }
+
+    /*
+     * Hàm cấu hình phí giao thức với mẫu số tùy chọn
+     *
+     * Mục đích: Cho phép tỉ lệ phí nhỏ hơn 1 basis point, ví dụ
+     * fee_denominator = 1_000_000 để tính theo phần triệu
+     * Phí = amount * fee_numerator / fee_denominator, tỉ lệ tối đa vẫn là 10%
+     * Trả về InvalidFeeRate nếu fee_denominator = 0 hoặc fee_numerator > fee_denominator
+     */
+    pub fn set_fee_rate(ctx: Context<SetFee>, fee_numerator: u32, fee_denominator: u32) -> Result<()> {
+        ctx.accounts.set(fee_numerator, fee_denominator)
+    }
+
+    /*
+     * Hàm cấu hình giới hạn số địa chỉ nhận mới của mỗi owner nguồn
+     *
+     * Mục đích: Hạn chế việc một owner gửi token tràn lan đến nhiều địa chỉ khác nhau
+     * (kiểu airdrop) trong window_seconds giây
+     * Đặt max_destinations_per_window = 0 để tắt
+     * Trả về InvalidWindow nếu window_seconds âm, hoặc bằng 0 khi giới hạn được bật
+     * Lưu ý: Khi bật, owner phải tạo bộ đếm bằng create_fan_out_counter trước khi chuyển token
+     */
+    pub fn set_fan_out_limit(
+        ctx: Context<SetFanOutLimit>,
+        max_destinations_per_window: u32,
+        window_seconds: i64
+    ) -> Result<()> {
+        // Chỉ authority mới có thể cấu hình giới hạn
+        if ctx.accounts.white_list.authority != ctx.accounts.signer.key() {
+            return err!(TransferError::Unauthorized);
+        }
+
+        if window_seconds < 0 || (max_destinations_per_window > 0 && window_seconds == 0) {
+            return err!(TransferError::InvalidWindow);
+        }
+
+        ctx.accounts.white_list.max_destinations_per_window = max_destinations_per_window;
+        ctx.accounts.white_list.fan_out_window_seconds = window_seconds;
+        msg!("Fan-out limit: {0} new destinations per {1} seconds", max_destinations_per_window, window_seconds);
+
+        Ok(())
+    }
+
+    /*
+     * Hàm tạo bộ đếm địa chỉ nhận mới cho một owner
+     *
+     * Mục đích: transfer hook không thể trả phí tạo account, nên bộ đếm phải
+     * được tạo trước. Bất kỳ ai cũng có thể trả phí tạo cho owner
+     */
+    pub fn create_fan_out_counter(ctx: Context<CreateFanOutCounter>, owner: Pubkey) -> Result<()> {
+        let fan_out_counter = &mut ctx.accounts.fan_out_counter;
+        fan_out_counter.owner = owner;
+        fan_out_counter.window_start = Clock::get()?.unix_timestamp;
+        fan_out_counter.count = 0;
+        fan_out_counter.recent_destinations = Vec::new();
+        msg!("Fan-out counter created for {0}", owner.to_string());
+
+        Ok(())
+    }
+
+    /*
+     * Hàm kiểm tra tính nhất quán của cấu hình
+     *
+     * Mục đích: Cho phép operator xác nhận trạng thái của whitelist hợp lệ
+     * bằng một lần gọi duy nhất (thường qua simulateTransaction)
+     * Trả về danh sách các vi phạm qua return data, rỗng nếu mọi thứ đều ổn
+     */
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<Vec<ConfigViolation>> {
+        // Kích thước thực tế của account để so sánh với dữ liệu đang lưu
+        let data_len = ctx.accounts.white_list.to_account_info().data_len();
+        let violations = ctx.accounts.white_list.config_violations(data_len);
+        msg!("Health check found {0} violation(s)", violations.len());
+
+        Ok(violations)
+    }
+
+    /*
+     * Hàm đọc toàn bộ cấu hình của whitelist
+     *
+     * Mục đích: Cho phép giao diện quản trị đọc authority, trạng thái tạm dừng, chế độ,
+     * các giới hạn và độ dài whitelist mà không cần giải mã dữ liệu thô của account
+     * Trả về kết quả qua return data (gọi bằng simulateTransaction)
+     */
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<WhitelistConfig> {
+        let data_len = ctx.accounts.white_list.to_account_info().data_len();
+        Ok(ctx.accounts.white_list.config(data_len))
+    }
+
+    /*
+     * Hàm kiểm tra một địa chỉ có trong whitelist hay không
+     *
+     * Mục đích: Cho phép frontend kiểm tra trước khi tạo giao dịch chuyển token
+     * Trả về kết quả qua return data (gọi bằng simulateTransaction)
+     */
+    pub fn is_whitelisted(ctx: Context<CheckWhiteList>, account: Pubkey) -> Result<bool> {
+        Ok(ctx.accounts.white_list.contains(&account))
+    }
+
+    /*
+     * Hàm kiểm tra thành viên dành cho program khác gọi qua CPI
+     *
+     * Mục đích: Cho phép program khác dùng whitelist làm điều kiện kiểm soát truy cập
+     * Trả về Ok nếu account có trong whitelist và chưa hết hạn, lỗi NotWhitelisted
+     * nếu không, program gọi chỉ cần dùng ? để hủy giao dịch của mình
+     * Cách gọi (bật feature "cpi" của crate transfer-hook):
+     *   transfer_hook::cpi::check_membership(
+     *       CpiContext::new(transfer_hook_program, transfer_hook::cpi::accounts::CheckMembership {
+     *           white_list,
+     *       }),
+     *       account,
+     *   )?;
+     * Lưu ý: Chỉ kiểm tra danh sách, không áp dụng các chính sách khác của transfer_hook
+     * (tạm dừng, chế độ DenyList/MerkleRoot, giới hạn số lượng)
+     */
+    pub fn check_membership(ctx: Context<CheckMembership>, account: Pubkey) -> Result<()> {
+        ctx.accounts.white_list.require_member(&account, Clock::get()?.unix_timestamp)
+    }
+
+    /*
+     * Hàm kiểm tra chủ sở hữu của một account token có trong whitelist hay không
+     *
+     * Mục đích: Cho phép UI chỉ có địa chỉ account token (ATA) mà không có địa chỉ ví
+     * kiểm tra trước với whitelist theo chủ sở hữu (MatchMode::Owner)
+     * Whitelist rỗng trả về false
+     * Trả về kết quả qua return data (gọi bằng simulateTransaction)
+     */
+    pub fn whitelist_contains_owner(ctx: Context<ContainsOwner>) -> Result<bool> {
+        Ok(ctx.accounts.white_list.contains(&ctx.accounts.destination_token.owner))
+    }
+
+    /*
+     * Hàm kiểm tra trước một giao dịch chuyển token có thành công hay không
+     *
+     * Mục đích: Cho phép UI giải thích lý do giao dịch bị chặn trước khi gửi
+     * Áp dụng cùng các chính sách của whitelist như transfer_hook (tạm dừng,
+     * danh sách, phía gửi, giới hạn số lượng) mà không cần account token
+     * source, destination: Địa chỉ ví (owner) của phía gửi và phía nhận
+     * Trả về kết quả qua return data (gọi bằng simulateTransaction), lý do bị chặn
+     * được ghi trong log cùng mã lỗi tương ứng
+     * Ở chế độ same_owner_only, source và destination phải là cùng một ví và kiểm tra
+     * thành viên được bỏ qua giống như transfer_hook
+     * Lưu ý: Không kiểm tra token thành viên và lịch giải phóng vì cần các account token
+     * và không kiểm tra bằng chứng Merkle ở chế độ MerkleRoot, attestation ở chế độ Attestation
+     */
+    pub fn can_transfer(
+        ctx: Context<CanTransfer>,
+        source: Pubkey,
+        destination: Pubkey,
+        amount: u64
+    ) -> Result<bool> {
+        if ctx.accounts.white_list.is_zero_amount_exempt(amount) || ctx.accounts.white_list.is_burn_exempt(&destination) {
+            return Ok(true);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let white_list = &ctx.accounts.white_list;
+        let result = if white_list.same_owner_only { white_list.check_same_owner(&source, &destination) } else { Ok(()) }
+            .and_then(|()| white_list.check_policy(&[source], &destination, None, amount, now));
+        match result {
+            Ok(()) => Ok(true),
+            Err(error) => {
+                msg!("Transfer would be rejected, reason code {0}", error_code_of(&error));
+                Ok(false)
+            }
+        }
+    }
+
+    /*
+     * Hàm lấy danh sách địa chỉ trong whitelist
+     *
+     * Mục đích: Cho phép client đọc whitelist qua return data (gọi bằng simulateTransaction)
+     * mà không phụ thuộc vào layout của account
+     * Whitelist rỗng trả về danh sách rỗng
+     * Lưu ý: return data tối đa 1024 byte, tức khoảng 31 địa chỉ
+     */
+    pub fn get_whitelist(ctx: Context<GetWhiteList>) -> Result<Vec<Pubkey>> {
+        Ok(ctx.accounts.white_list.white_list.iter().map(|entry| entry.account).collect())
+    }
+
+    /*
+     * Hàm lấy một trang của whitelist
+     *
+     * Mục đích: Whitelist lớn không vừa return data của get_whitelist, client
+     * đọc lần lượt từng trang white_list[offset..offset + limit]
+     * Trang được giới hạn trong vector, offset vượt quá cuối trả về danh sách rỗng
+     * Lưu ý: limit lớn hơn MAX_RETURN_DATA_KEYS được giảm xuống MAX_RETURN_DATA_KEYS
+     */
+    pub fn get_whitelist_page(ctx: Context<GetWhiteListPage>, offset: u32, limit: u32) -> Result<Vec<Pubkey>> {
+        Ok(ctx.accounts.white_list.page(offset, limit))
+    }
+
+    /*
+     * Hàm lấy kích thước của account ExtraAccountMetaList
+     *
+     * Mục đích: Client dùng giá trị này để tính rent cần cấp cho
+     * initialize_extra_account_meta_list thay vì tự tính lại size_of,
+     * luôn khớp với danh sách account bổ sung hiện tại của program
+     */
+    pub fn extra_account_meta_list_size(_ctx: Context<ExtraAccountMetaListSize>) -> Result<u64> {
+        Ok(InitializeExtraAccountMetaList::extra_account_meta_list_size()? as u64)
+    }
+
+    /*
+     * Hàm lấy số địa chỉ trong whitelist
+     *
+     * Mục đích: Cho client biết cần đọc bao nhiêu trang bằng get_whitelist_page
+     */
+    pub fn get_whitelist_len(ctx: Context<GetWhiteList>) -> Result<u32> {
+        Ok(ctx.accounts.white_list.white_list.len() as u32)
+    }
+
+    /*
+     * Hàm lấy số địa chỉ còn có thể thêm vào whitelist
+     *
+     * Mục đích: Cho công cụ nhập hàng loạt biết có cần gọi resize_whitelist/grow_whitelist
+     * trước khi nhập hay không. Tính theo max_entries và kích thước thực tế của account
+     * Trả về 0 (không phải lỗi) khi whitelist đã đầy
+     */
+    pub fn remaining_capacity(ctx: Context<RemainingCapacity>) -> Result<u32> {
+        let data_len = ctx.accounts.white_list.to_account_info().data_len();
+        Ok(ctx.accounts.white_list.remaining_capacity(data_len))
+    }
+
+    /*
+     * Hàm tìm các địa chỉ chưa có trong whitelist
+     *
+     * Mục đích: Khi nhận một danh sách địa chỉ mới, chỉ cần thêm phần chênh lệch
+     * Trả về các phần tử của candidates chưa có trong whitelist, theo thứ tự ban đầu,
+     * qua return data (gọi bằng simulateTransaction)
+     * Lưu ý: Chỉ trả về tối đa MAX_RETURN_DATA_KEYS địa chỉ, tổng số địa chỉ còn thiếu
+     * được ghi trong log để client gọi lại với phần còn lại của danh sách
+     */
+    pub fn find_missing(ctx: Context<FindMissing>, candidates: Vec<Pubkey>) -> Result<Vec<Pubkey>> {
+        let white_list = &ctx.accounts.white_list;
+        let mut missing: Vec<Pubkey> = Vec::new();
+        for candidate in candidates {
+            // Bỏ qua địa chỉ đã có trong whitelist hoặc bị lặp trong đầu vào
+            if white_list.contains(&candidate) || missing.contains(&candidate) {
+                continue;
+            }
+            missing.push(candidate);
+        }
+        let total = missing.len();
+        missing.truncate(MAX_RETURN_DATA_KEYS);
+        msg!("{0} candidate(s) missing from white list, returning {1}", total, missing.len());
+
+        Ok(missing)
+    }
+}
+
+// PHẦN 6: HÀM HELPER
+/*
+ * Tra cứu một địa chỉ trong whitelist đã được sắp xếp bằng tìm kiếm nhị phân
+ *
+ * Dùng cho mọi tra cứu của whitelist, kể cả trong transfer_hook (check_policy)
+ * Ở bản build có debug_assertions (unit test), danh sách được kiểm tra thực sự đã
+ * sắp xếp để lỗi ở đường thêm địa chỉ bị phát hiện thay vì trả về kết quả sai
+ */
+fn sorted_position(list: &[WhiteListEntry], key: &Pubkey) -> Option<usize> {
+    debug_assert!(
+        list.windows(2).all(|pair| pair[0].account < pair[1].account),
+        "white list entries must be sorted by account"
+    );
+    list.binary_search_by(|entry| entry.account.cmp(key)).ok()
+}
+
+// Kiểm tra một địa chỉ có trong whitelist đã được sắp xếp không
+fn is_whitelisted_fast(list: &[WhiteListEntry], key: &Pubkey) -> bool {
+    sorted_position(list, key).is_some()
+}
+
+/*
+ * Ghi các địa chỉ bị xóa vào nhật ký xóa nếu record_removals được bật
+ *
+ * Dùng chung cho các hàm xóa khỏi whitelist
+ */
+fn record_removals(
+    white_list: &WhiteList,
+    audit: &mut Option<Account<RemovalAudit>>,
+    accounts: &[Pubkey]
+) -> Result<()> {
+    if !white_list.record_removals || accounts.is_empty() {
+        return Ok(());
+    }
+    match audit {
+        Some(audit) => {
+            let capacity = RemovalAudit::capacity_for(audit.to_account_info().data_len());
+            audit.record(accounts, Clock::get()?.unix_timestamp, capacity)
+        }
+        None => err!(TransferError::AuditLogRequired),
+    }
+}
+
+/*
+ * Hàm thực hiện các kiểm tra của transfer hook
+ *
+ * Trả về lỗi đầu tiên gặp phải, transfer_hook dùng kết quả này
+ * để phát sự kiện TransferDecision trước khi trả về
+ */
+fn evaluate_transfer(ctx: &Context<TransferHook>, amount: u64) -> Result<()> {
+    // Phòng thủ nhiều lớp: ràng buộc token::mint đã kiểm tra điều này,
+    // kiểm tra lại tường minh để bất biến nằm ngay trong thân instruction
+    check_token_mints(
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.source_token.mint,
+        &ctx.accounts.destination_token.mint
+    )?;
+
+    // Kiểm tra xem hàm có được gọi trong ngữ cảnh chuyển token không
+    // Đây là biện pháp bảo mật quan trọng để ngăn chặn việc gọi trực tiếp vào hàm này
+    check_is_transferring(ctx)?;
+
+    // Phòng thủ nhiều lớp: không cho phép giao dịch dựa trên một whitelist
+    // toàn 0 hoặc không thuộc program này (ví dụ khi ExtraAccountMetaList bị cấu hình sai)
+    ctx.accounts.white_list.check_initialized(ctx.accounts.white_list.to_account_info().owner)?;
+    check_meta_list_initialized(&ctx.accounts.extra_account_meta_list)?;
+
+    // Giao dịch 0 token bỏ qua các kiểm tra whitelist nếu được cấu hình
+    // (check_is_transferring ở trên vẫn bắt buộc)
+    if ctx.accounts.white_list.is_zero_amount_exempt(amount) {
+        msg!("Zero-amount transfer, whitelist checks skipped");
+        return Ok(());
+    }
+
+    // Giao dịch đốt token (gửi đến incinerator) bỏ qua các kiểm tra whitelist nếu được cấu hình
+    if ctx.accounts.white_list.is_burn_exempt(&ctx.accounts.destination_token.owner) {
+        msg!("Burn to incinerator, whitelist checks skipped");
+        return Ok(());
+    }
+
+    // Chặn giao dịch tự chuyển nếu được cấu hình
+    ctx.accounts.white_list.check_self_transfer(
+        &ctx.accounts.source_token.key(),
+        &ctx.accounts.destination_token.key()
+    )?;
+
+    // Ở chế độ same_owner_only, hai account phải thuộc cùng một ví
+    // Chỉ kiểm tra thành viên whitelist được bỏ qua (trong check_policy và Merkle/attestation),
+    // giới hạn số lượng, lịch giải phóng và các bộ đếm bên dưới vẫn được áp dụng
+    let same_owner_only = ctx.accounts.white_list.same_owner_only;
+    if same_owner_only {
+        ctx.accounts.white_list.check_same_owner(&ctx.accounts.source_token.owner, &ctx.accounts.destination_token.owner)?;
+    }
+
+    // Chặn chuyển vào account token vừa được tạo nếu được cấu hình
+    ctx.accounts.white_list.check_destination_exists(
+        &ctx.accounts.destination_token.key(),
+        ctx.accounts.destination_token.amount,
+        amount
+    )?;
+
+    // Các chính sách của whitelist: tạm dừng, danh sách, phía gửi, giới hạn số lượng
+    // Phía gửi hợp lệ nếu owner hoặc account token nguồn có trong whitelist
+    // Phía nhận là chủ sở hữu hoặc account token đích tùy theo match_mode
+    ctx.accounts.white_list.check_policy(
+        &[ctx.accounts.owner.key(), ctx.accounts.source_token.key()],
+        &ctx.accounts.white_list.destination_key(
+            &ctx.accounts.destination_token.key(),
+            &ctx.accounts.destination_token.owner
+        ),
+        destination_vault_program(ctx)?,
+        amount,
+        Clock::get()?.unix_timestamp
+    )?;
+
+    if !same_owner_only {
+        // Ở chế độ MerkleRoot, chủ sở hữu account đích phải có bằng chứng đã xác minh
+        check_merkle_membership(ctx)?;
+
+        // Ở chế độ Attestation, chủ sở hữu account đích phải có attestation còn hiệu lực
+        check_attestation(ctx)?;
+    }
+
+    // Kiểm tra chủ sở hữu account đích có nắm giữ đủ token thành viên không
+    check_membership_balance(ctx)?;
+
+    // Kiểm tra chủ sở hữu account đích có nắm giữ mint bắt buộc không
+    check_required_mint(ctx)?;
+
+    // Áp dụng lịch giải phóng nếu account nguồn hoặc đích là escrow
+    apply_release_schedules(ctx, amount)?;
+
+    // Áp dụng giới hạn tổng số token account đích được nhận
+    apply_receiving_limit(ctx, amount)?;
+
+    // Áp dụng giới hạn tần suất của owner nguồn
+    apply_rate_limit(ctx)?;
+
+    // Áp dụng quota mỗi epoch của owner nguồn
+    apply_epoch_quota(ctx, amount)?;
+
+    // Áp dụng giới hạn số địa chỉ nhận mới của owner nguồn
+    apply_fan_out_limit(ctx)?;
+
+    // Ghi nhận phí giao thức vào treasury
+    apply_fee(ctx, amount)?;
+
+    // Log thông báo thành công khi account đích vượt qua mọi kiểm tra
+    msg!("Account in white list, all good!");
+
+    Ok(())
+}
+
+// Lấy mã lỗi số từ một lỗi Anchor, dùng cho reason_code của TransferDecision
+fn error_code_of(error: &Error) -> u32 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => {
+            // Lỗi có sẵn của runtime được mã hóa ở 32 bit cao, lỗi custom ở 32 bit thấp
+            let code = u64::from(error.program_error.clone());
+            if code > u32::MAX as u64 { (code >> 32) as u32 } else { code as u32 }
+        }
+    }
+}
+
+/*
+ * Hàm kiểm tra trạng thái chuyển token
+ * 
+ * Đây là hàm helper để đảm bảo rằng hàm transfer_hook
+ * chỉ được gọi bởi Token-2022 trong ngữ cảnh chuyển token
+ * Đây là một biện pháp bảo mật quan trọng
+ */
+fn check_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
+    // Feature skip-transferring-check chỉ dành cho kiểm thử với các test validator
+    // không đặt cờ transferring đúng cách. KHÔNG BAO GIỜ bật feature này khi triển khai
+    // thật: bất kỳ ai cũng có thể gọi trực tiếp transfer_hook để thay đổi trạng thái
+    if cfg!(feature = "skip-transferring-check") {
+        msg!("WARNING: transferring check is disabled (skip-transferring-check)");
+        return Ok(());
+    }
+    // Lấy thông tin account token nguồn
+    let source_token_info = ctx.accounts.source_token.to_account_info();
+    // Mượn dữ liệu của account để đọc và chỉnh sửa
+    let mut account_data_ref: RefMut<&mut [u8]> = source_token_info.try_borrow_mut_data()?;
+    let result = check_is_transferring_data(&mut account_data_ref);
+    // Ghi lại lần gọi trực tiếp để giám sát, lỗi vẫn được trả về như cũ
+    if result.is_err() {
+        emit!(DirectCallAttempt {
+            source: ctx.accounts.source_token.key(),
+            owner: ctx.accounts.owner.key(),
+        });
+    }
+    result
+}
+
+/*
+ * Kiểm tra trạng thái "transferring" từ dữ liệu thô của account token
+ *
+ * Tách riêng khỏi check_is_transferring để có thể kiểm thử mà không cần Context
+ */
+fn check_is_transferring_data(account_data: &mut [u8]) -> Result<()> {
+    // Giải mã dữ liệu account thành cấu trúc PodAccount
+    // PodStateWithExtensionsMut cho phép truy cập vào dữ liệu của account
+    // và các extension của nó
+    let mut account = PodStateWithExtensionsMut::<PodAccount>::unpack(account_data)?;
+    // Lấy extension TransferHookAccount từ account
+    // TransferHookAccount chứa trạng thái của quá trình chuyển token
+    // account thiếu extension này không thể đang được chuyển qua transfer hook,
+    // trả về lỗi rõ ràng thay vì lỗi chung của SPL
+    let Ok(account_extension) = account.get_extension_mut::<TransferHookAccount>() else {
+        msg!("Source token account has no TransferHookAccount extension");
+        return err!(TransferError::IsNotCurrentlyTransferring);
+    };
+
+    // Kiểm tra trạng thái "transferring"
     // Nếu không phải đang chuyển token (transferring = false) thì báo lỗi
     // Điều này ngăn chặn việc gọi trực tiếp vào hàm transfer_hook
     if !bool::from(account_extension.transferring) {
@@ -385,3 +6954,1816 @@ fn check_is_transferring(ctx: &Context<TransferHook>) -> Result<()> {
 
     Ok(())
 }
+
+/*
+ * Program sở hữu chủ sở hữu account đích, nếu đã được đăng ký bằng register_program_vault
+ *
+ * Trả về None khi không có program nào được phép hoặc account chưa được tạo
+ */
+fn destination_vault_program(ctx: &Context<TransferHook>) -> Result<Option<Pubkey>> {
+    if ctx.accounts.white_list.allowed_owner_programs.is_empty() {
+        return Ok(None);
+    }
+    let vault_info = ctx.accounts.destination_program_vault.to_account_info();
+    if !is_initialized_program_account(&vault_info) {
+        return Ok(None);
+    }
+    let data = vault_info.try_borrow_data()?;
+    let vault = ProgramVault::try_deserialize(&mut &data[..])?;
+    Ok(Some(vault.program))
+}
+
+/*
+ * Hàm kiểm tra bằng chứng Merkle của chủ sở hữu account đích
+ *
+ * Chỉ áp dụng ở chế độ MerkleRoot: account MerkleMembership phải tồn tại
+ * và được xác minh với merkle_root hiện tại
+ */
+fn check_merkle_membership(ctx: &Context<TransferHook>) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    // bypass_sender không cần bằng chứng của phía nhận
+    if white_list.mode != WhitelistMode::MerkleRoot || white_list.is_bypass_sender(&[ctx.accounts.owner.key()]) {
+        return Ok(());
+    }
+
+    let membership_info = ctx.accounts.destination_merkle_membership.to_account_info();
+    if is_initialized_program_account(&membership_info) {
+        let data = membership_info.try_borrow_data()?;
+        let membership = MerkleMembership::try_deserialize(&mut &data[..])?;
+        if membership.root == white_list.merkle_root {
+            return Ok(());
+        }
+    }
+
+    msg!("No Merkle proof for {0} under the current root", ctx.accounts.destination_token.owner.to_string());
+    err!(TransferError::InvalidMerkleProof)
+}
+
+/*
+ * Hàm kiểm tra attestation của chủ sở hữu account đích
+ *
+ * Chỉ áp dụng ở chế độ Attestation, bypass_sender không cần attestation của phía nhận
+ */
+fn check_attestation(ctx: &Context<TransferHook>) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    if white_list.mode != WhitelistMode::Attestation || white_list.is_bypass_sender(&[ctx.accounts.owner.key()]) {
+        return Ok(());
+    }
+
+    white_list.check_attestation(
+        &ctx.accounts.destination_attestation,
+        &ctx.accounts.destination_token.owner,
+        Clock::get()?.unix_timestamp
+    )
+}
+
+// Lá của cây Merkle cho một địa chỉ ví
+fn merkle_leaf(owner: &Pubkey) -> [u8; 32] {
+    hashv(&[owner.as_ref()]).to_bytes()
+}
+
+/*
+ * Hàm xác minh bằng chứng Merkle
+ *
+ * Mỗi bước hash cặp (nút hiện tại, nút anh em) sau khi sắp xếp,
+ * nên bằng chứng không cần chứa vị trí trái/phải
+ */
+fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+/*
+ * Hàm kiểm tra số dư token thành viên
+ *
+ * Nếu cấu hình yêu cầu (required_balance > 0), chủ sở hữu account đích
+ * phải có account token của required_mint với số dư đủ lớn
+ * account token chưa tồn tại được coi là số dư 0
+ */
+fn check_membership_balance(ctx: &Context<TransferHook>) -> Result<()> {
+    let config = &ctx.accounts.membership_config;
+    // Tính năng bị tắt
+    if config.required_balance == 0 {
+        return Ok(());
+    }
+
+    let balance = holding_balance(
+        config,
+        &ctx.accounts.membership_token.to_account_info(),
+        &ctx.accounts.destination_token.owner
+    )?;
+    if balance < config.required_balance {
+        msg!("Membership balance {0} is below the required {1}", balance, config.required_balance);
+        return err!(TransferError::InsufficientMembershipBalance);
+    }
+
+    Ok(())
+}
+
+/*
+ * Hàm kiểm tra chủ sở hữu account đích nắm giữ mint bắt buộc
+ *
+ * Chỉ áp dụng khi WhiteList::required_mint được đặt, số dư phải khác 0
+ */
+fn check_required_mint(ctx: &Context<TransferHook>) -> Result<()> {
+    let required_mint = match ctx.accounts.white_list.required_mint {
+        Some(required_mint) => required_mint,
+        // Tính năng bị tắt
+        None => return Ok(()),
+    };
+
+    let config = &ctx.accounts.required_mint_config;
+    let balance = if config.required_mint == required_mint {
+        holding_balance(
+            config,
+            &ctx.accounts.required_mint_token.to_account_info(),
+            &ctx.accounts.destination_token.owner
+        )?
+    } else {
+        0
+    };
+    if balance == 0 {
+        msg!("Destination owner does not hold {0}", required_mint.to_string());
+        return err!(TransferError::MissingRequiredMint);
+    }
+
+    Ok(())
+}
+
+/*
+ * Số dư token của mint trong config mà owner nắm giữ trong account token_info
+ *
+ * Trả về 0 nếu account chưa tồn tại, không thuộc đúng token program,
+ * khác mint hoặc thuộc về chủ sở hữu khác
+ */
+fn holding_balance(config: &MembershipConfig, token_info: &AccountInfo, owner: &Pubkey) -> Result<u64> {
+    if token_info.owner != &config.required_mint_token_program || token_info.data_is_empty() {
+        return Ok(0);
+    }
+    let data = token_info.try_borrow_data()?;
+    let token = TokenAccount::try_deserialize(&mut &data[..])?;
+    // Đảm bảo account token đúng mint và thuộc về chủ sở hữu account đích
+    if token.mint == config.required_mint && token.owner == *owner {
+        Ok(token.amount)
+    } else {
+        Ok(0)
+    }
+}
+
+/*
+ * Hàm áp dụng lịch giải phóng của escrow
+ *
+ * - Chuyển RA khỏi escrow: số lượng không được vượt quá phần đã giải phóng
+ *   nhưng chưa rút, sau đó cộng vào withdrawn
+ * - Chuyển VÀO escrow: cộng vào total_deposited
+ * Các account lịch chưa được tạo sẽ được bỏ qua
+ */
+fn apply_release_schedules(ctx: &Context<TransferHook>, amount: u64) -> Result<()> {
+    let source_info = ctx.accounts.source_release_schedule.to_account_info();
+    if is_initialized_program_account(&source_info) {
+        let mut data = source_info.try_borrow_mut_data()?;
+        let mut schedule = ReleaseSchedule::try_deserialize(&mut &data[..])?;
+        schedule.withdraw(amount, Clock::get()?.unix_timestamp)?;
+        schedule.try_serialize(&mut &mut data[..])?;
+    }
+
+    let destination_info = ctx.accounts.destination_release_schedule.to_account_info();
+    if is_initialized_program_account(&destination_info) {
+        let mut data = destination_info.try_borrow_mut_data()?;
+        let mut schedule = ReleaseSchedule::try_deserialize(&mut &data[..])?;
+        schedule.total_deposited = schedule.total_deposited.saturating_add(amount);
+        schedule.try_serialize(&mut &mut data[..])?;
+    }
+
+    Ok(())
+}
+
+/*
+ * Hàm áp dụng giới hạn nhận token của account đích
+ *
+ * Cộng amount vào received, trả về lỗi nếu vượt quá cap
+ * account giới hạn chưa được tạo sẽ được bỏ qua
+ */
+fn apply_receiving_limit(ctx: &Context<TransferHook>, amount: u64) -> Result<()> {
+    let limit_info = ctx.accounts.destination_receiving_limit.to_account_info();
+    if is_initialized_program_account(&limit_info) {
+        let mut data = limit_info.try_borrow_mut_data()?;
+        let mut limit = ReceivingLimit::try_deserialize(&mut &data[..])?;
+        let received = limit.received.saturating_add(amount as u128);
+        if received > limit.cap {
+            msg!("Destination received {0} of cap {1}, attempted {2}", limit.received, limit.cap, amount);
+            return err!(TransferError::ReceivingCapExceeded);
+        }
+        limit.received = received;
+        limit.try_serialize(&mut &mut data[..])?;
+    }
+
+    Ok(())
+}
+
+/*
+ * Hàm áp dụng giới hạn tần suất của owner nguồn
+ *
+ * Khi được bật, bộ đếm của owner phải tồn tại, nếu không giao dịch bị từ chối
+ * (nếu bỏ qua bộ đếm chưa tạo, owner có thể né giới hạn bằng cách không tạo)
+ */
+fn apply_rate_limit(ctx: &Context<TransferHook>) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    if white_list.max_per_window == 0 {
+        return Ok(());
+    }
+
+    let counter_info = ctx.accounts.source_rate_counter.to_account_info();
+    if !is_initialized_program_account(&counter_info) {
+        msg!("Rate counter not found for {0}, call create_rate_counter first", ctx.accounts.owner.key().to_string());
+        return err!(TransferError::RateLimited);
+    }
+    let mut data = counter_info.try_borrow_mut_data()?;
+    let mut counter = RateCounter::try_deserialize(&mut &data[..])?;
+    if !counter.record(Clock::get()?.unix_timestamp, white_list.max_per_window, white_list.window_seconds) {
+        msg!("Owner exceeded {0} transfers per {1} seconds", white_list.max_per_window, white_list.window_seconds);
+        return err!(TransferError::RateLimited);
+    }
+    counter.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/*
+ * Hàm áp dụng quota mỗi epoch của owner nguồn
+ *
+ * Khi được bật, bộ đếm của owner phải tồn tại, nếu không giao dịch bị từ chối
+ * (nếu bỏ qua bộ đếm chưa tạo, owner có thể né quota bằng cách không tạo)
+ */
+fn apply_epoch_quota(ctx: &Context<TransferHook>, amount: u64) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    if white_list.per_epoch_quota == 0 {
+        return Ok(());
+    }
+
+    let quota_info = ctx.accounts.source_epoch_quota.to_account_info();
+    if !is_initialized_program_account(&quota_info) {
+        msg!("Epoch quota counter not found for {0}, call create_epoch_quota first", ctx.accounts.owner.key().to_string());
+        return err!(TransferError::EpochQuotaExceeded);
+    }
+    let mut data = quota_info.try_borrow_mut_data()?;
+    let mut quota = EpochQuota::try_deserialize(&mut &data[..])?;
+    if !quota.record(Clock::get()?.epoch, amount, white_list.per_epoch_quota) {
+        msg!("Owner moved {0}, exceeding {1} per epoch", quota.moved, white_list.per_epoch_quota);
+        return err!(TransferError::EpochQuotaExceeded);
+    }
+    quota.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/*
+ * Hàm ghi nhận phí giao thức của giao dịch vào treasury
+ *
+ * Khi phí được bật, treasury phải tồn tại, nếu không giao dịch bị từ chối
+ */
+fn apply_fee(ctx: &Context<TransferHook>, amount: u64) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    if white_list.fee_numerator == 0 {
+        return Ok(());
+    }
+
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    if !is_initialized_program_account(&treasury_info) {
+        msg!("Treasury not found, call set_fee_basis_points first");
+        return err!(TransferError::TreasuryNotInitialized);
+    }
+    let fee = white_list.fee_for(amount)?;
+    let mut data = treasury_info.try_borrow_mut_data()?;
+    let mut treasury = Treasury::try_deserialize(&mut &data[..])?;
+    treasury.accrue(fee)?;
+    treasury.try_serialize(&mut &mut data[..])?;
+    msg!("Fee accrued: {0}, total: {1}", fee, treasury.accrued_fees);
+
+    Ok(())
+}
+
+/*
+ * Hàm áp dụng giới hạn số địa chỉ nhận mới của owner nguồn
+ *
+ * Địa chỉ nhận được tính theo chủ sở hữu của account token đích
+ * Khi được bật, bộ đếm của owner phải tồn tại, nếu không giao dịch bị từ chối
+ */
+fn apply_fan_out_limit(ctx: &Context<TransferHook>) -> Result<()> {
+    let white_list = &ctx.accounts.white_list;
+    if white_list.max_destinations_per_window == 0 {
+        return Ok(());
+    }
+
+    let counter_info = ctx.accounts.source_fan_out.to_account_info();
+    if !is_initialized_program_account(&counter_info) {
+        msg!("Fan-out counter not found for {0}, call create_fan_out_counter first", ctx.accounts.owner.key().to_string());
+        return err!(TransferError::FanOutLimitExceeded);
+    }
+    let mut data = counter_info.try_borrow_mut_data()?;
+    let mut counter = FanOutCounter::try_deserialize(&mut &data[..])?;
+    if !counter.record(
+        ctx.accounts.destination_token.owner,
+        Clock::get()?.unix_timestamp,
+        white_list.max_destinations_per_window,
+        white_list.fan_out_window_seconds
+    ) {
+        msg!(
+            "Owner sent to more than {0} new destinations per {1} seconds",
+            white_list.max_destinations_per_window,
+            white_list.fan_out_window_seconds
+        );
+        return err!(TransferError::FanOutLimitExceeded);
+    }
+    counter.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/*
+ * Kiểm tra quyền thêm/xóa địa chỉ trong whitelist
+ *
+ * Được phép nếu signer là authority hoặc admin, hoặc signer là PDA seeds
+ * ["program_authority", mint] của program_authority. Chỉ program_authority mới
+ * ký được cho PDA này (invoke_signed), nên program khác gọi ở bất kỳ độ sâu CPI nào
+ * cũng không thể giả mạo
+ * Lưu ý: program_authority chịu trách nhiệm tự kiểm tra quyền của người gọi nó
+ */
+fn is_authorized_manager(white_list: &WhiteList, mint: &Pubkey, signer: &Pubkey) -> bool {
+    white_list.is_manager(signer) ||
+        white_list.program_authority.is_some_and(|program| program_authority_signer(&program, mint) == *signer)
+}
+
+// PDA mà program_authority dùng để ký khi quản lý whitelist của mint qua CPI
+pub fn program_authority_signer(program_authority: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_AUTHORITY_SEED, mint.as_ref()], program_authority).0
+}
+
+// Quy đổi số lượng token nguyên sang đơn vị cơ sở: whole * 10^decimals
+fn whole_tokens_to_base_units(whole: u64, decimals: u8) -> Result<u64> {
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| whole.checked_mul(scale))
+        .ok_or(error!(TransferError::AmountOverflow))
+}
+
+// Kiểm tra account của authority mới đúng là new_authority và thuộc sở hữu của verify_owner
+fn check_authority_target(account: &AccountInfo, new_authority: &Pubkey, verify_owner: &Pubkey) -> Result<()> {
+    if account.key != new_authority || account.owner != verify_owner {
+        msg!("New authority {0} is owned by {1}", account.key.to_string(), account.owner.to_string());
+        return err!(TransferError::InvalidAuthorityTarget);
+    }
+    Ok(())
+}
+
+// Kiểm tra account token nguồn và đích cùng thuộc mint của giao dịch
+fn check_token_mints(mint: &Pubkey, source_mint: &Pubkey, destination_mint: &Pubkey) -> Result<()> {
+    for token_mint in [source_mint, destination_mint] {
+        if token_mint != mint {
+            msg!("Token account mint {0} does not match {1}", token_mint.to_string(), mint.to_string());
+            return err!(TransferError::MintMismatch);
+        }
+    }
+    Ok(())
+}
+
+// Kiểm tra account ExtraAccountMetaList đã được khởi tạo
+// (initialize_extra_account_meta_list chưa được gọi thì account trống)
+fn check_meta_list_initialized(info: &AccountInfo) -> Result<()> {
+    if !is_initialized_program_account(info) {
+        msg!("Extra account meta list {0} is not initialized", info.key.to_string());
+        return err!(TransferError::MetaListNotInitialized);
+    }
+    Ok(())
+}
+
+// Kiểm tra account đã được tạo và thuộc sở hữu của program này
+fn is_initialized_program_account(info: &AccountInfo) -> bool {
+    info.owner == &crate::ID && !info.data_is_empty()
+}
+
+/*
+ * Hàm ghi lại ExtraAccountMetaList theo extra_account_metas() hiện tại
+ *
+ * Account được tăng hoặc giảm kích thước cho vừa danh sách mới, payer trả thêm rent khi cần
+ * Dùng chung cho update_extra_account_meta_list và set_attestation_program
+ */
+fn rewrite_extra_account_meta_list<'info>(
+    meta_list_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    attestation_program: Option<Pubkey>
+) -> Result<()> {
+    let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas(attestation_program)?;
+    let new_len = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+    let old_len = meta_list_info.data_len();
+    if new_len > old_len {
+        if new_len - old_len > MAX_PERMITTED_DATA_INCREASE {
+            return err!(TransferError::ReallocFailed);
+        }
+        grow_program_account(meta_list_info, payer, system_program, new_len)?;
+    }
+
+    ExtraAccountMetaList::update::<ExecuteInstruction>(
+        &mut meta_list_info.try_borrow_mut_data()?,
+        &extra_account_metas
+    )?;
+    // Bỏ phần dữ liệu thừa nếu danh sách mới ngắn hơn
+    if new_len < old_len {
+        meta_list_info.resize(new_len)?;
+    }
+    msg!("Extra account meta list updated with {0} accounts", extra_account_metas.len());
+
+    Ok(())
+}
+
+/*
+ * Hàm tăng kích thước một account thuộc program
+ *
+ * payer chuyển thêm lamports để account vẫn được miễn rent với kích thước mới
+ * Phần bộ nhớ tăng thêm được runtime khởi tạo bằng 0
+ * Trả về InsufficientRentTopUp nếu payer không đủ lamports hoặc account vẫn chưa
+ * được miễn rent sau khi tăng kích thước
+ */
+fn grow_program_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize
+) -> Result<()> {
+    let rent = Rent::get()?;
+    let top_up = rent_top_up(&rent, account.lamports(), payer.lamports(), new_len)?;
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(system_program.to_account_info(), Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            }),
+            top_up
+        )?;
+    }
+    account.resize(new_len)?;
+
+    // Kiểm tra lại sau khi tăng kích thước, account thiếu rent sẽ bị runtime thu hồi
+    if !rent.is_exempt(account.lamports(), new_len) {
+        return err!(TransferError::InsufficientRentTopUp);
+    }
+
+    Ok(())
+}
+
+/*
+ * Tính số lamports cần nạp thêm để account có kích thước new_len được miễn rent
+ *
+ * Trả về InsufficientRentTopUp nếu payer không đủ lamports để nạp
+ */
+fn rent_top_up(rent: &Rent, lamports: u64, payer_lamports: u64, new_len: usize) -> Result<u64> {
+    let top_up = rent.minimum_balance(new_len).saturating_sub(lamports);
+    if top_up > payer_lamports {
+        msg!("Rent top-up of {0} lamports exceeds payer balance {1}", top_up, payer_lamports);
+        return err!(TransferError::InsufficientRentTopUp);
+    }
+    Ok(top_up)
+}
+
+// Số lamports vượt quá mức miễn rent của một account có kích thước data_len
+fn rent_surplus(rent: &Rent, lamports: u64, data_len: usize) -> u64 {
+    lamports.saturating_sub(rent.minimum_balance(data_len))
+}
+
+/*
+ * Hàm đóng một account thuộc program
+ *
+ * Toàn bộ lamports được chuyển cho destination, account được trả về
+ * cho System Program với dữ liệu rỗng để không thể dùng lại
+ */
+fn close_program_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(TransferError::AmountOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.resize(0)?;
+
+    Ok(())
+}
+
+// PHẦN 7: HELPER CHO CLIENT
+/*
+ * Các hàm hỗ trợ client Rust suy ra PDA và tạo instruction
+ *
+ * Chỉ được biên dịch ngoài môi trường on-chain, không ảnh hưởng đến program được triển khai
+ * program_id được truyền vào để dùng được với các bản triển khai có địa chỉ khác
+ */
+#[cfg(not(target_os = "solana"))]
+pub mod client {
+    use super::*;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use anchor_lang::{ InstructionData, ToAccountMetas };
+
+    // PDA whitelist của mint
+    pub fn whitelist_pda(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[WHITE_LIST_SEED, mint.as_ref()], program_id)
+    }
+
+    // PDA ExtraAccountMetaList của mint
+    pub fn extra_account_metas_pda(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], program_id)
+    }
+
+    // Tạo instruction initialize_extra_account_meta_list, payer trở thành authority của whitelist
+    pub fn initialize_extra_account_meta_list_ix(
+        program_id: &Pubkey,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        max_entries: u16
+    ) -> Instruction {
+        let accounts = crate::accounts::InitializeExtraAccountMetaList {
+            payer: *payer,
+            extra_account_meta_list: extra_account_metas_pda(program_id, mint).0,
+            mint: *mint,
+            system_program: System::id(),
+            white_list: whitelist_pda(program_id, mint).0,
+            membership_config: Pubkey::find_program_address(&[MEMBERSHIP_SEED, mint.as_ref()], program_id).0,
+            required_mint_config: Pubkey::find_program_address(&[REQUIRED_MINT_SEED, mint.as_ref()], program_id).0,
+        };
+        Instruction {
+            program_id: *program_id,
+            accounts: accounts.to_account_metas(None),
+            data: crate::instruction::InitializeExtraAccountMetaList { max_entries }.data(),
+        }
+    }
+
+    // Tạo instruction add_to_whitelist, signer phải là authority hoặc admin của whitelist
+    // (hoặc PDA của program_authority, xem program_authority_signer)
+    pub fn add_to_whitelist_ix(program_id: &Pubkey, mint: &Pubkey, new_account: &Pubkey, signer: &Pubkey) -> Instruction {
+        let accounts = crate::accounts::AddToWhiteList {
+            mint: *mint,
+            new_account: *new_account,
+            white_list: whitelist_pda(program_id, mint).0,
+            signer: *signer,
+        };
+        Instruction {
+            program_id: *program_id,
+            accounts: accounts.to_account_metas(None),
+            data: crate::instruction::AddToWhitelist {}.data(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::{
+        extension::{ BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut },
+        state::{ Account, AccountState },
+    };
+    use anchor_lang::solana_program::program_pack::Pack;
+    use proptest::prelude::*;
+
+    // Tạo dữ liệu account token, có hoặc không có extension TransferHookAccount
+    fn token_account_data(transferring: Option<bool>) -> Vec<u8> {
+        let extensions = match transferring {
+            Some(_) => vec![ExtensionType::TransferHookAccount],
+            None => vec![],
+        };
+        let len = ExtensionType::try_calculate_account_len::<Account>(&extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state.base = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        state.pack_base();
+        if let Some(transferring) = transferring {
+            state.init_account_type().unwrap();
+            let extension = state.init_extension::<TransferHookAccount>(true).unwrap();
+            extension.transferring = transferring.into();
+        }
+        data
+    }
+
+    // Whitelist với cấu hình mặc định
+    fn white_list() -> WhiteList {
+        WhiteList {
+            version: WhiteList::VERSION,
+            authority: Pubkey::new_unique(),
+            white_list: Vec::new(),
+            pending_authority: None,
+            paused: false,
+            check_source: false,
+            mode: WhitelistMode::AllowList,
+            max_transfer_amount: None,
+            transfer_count: 0,
+            total_volume: 0,
+            activation_delay: 0,
+            max_entries: 0,
+            admins: Vec::new(),
+            name: [0; 32],
+            block_self_transfer: false,
+            merkle_root: [0; 32],
+            max_per_window: 0,
+            window_seconds: 0,
+            white_list_bump: 0,
+            meta_list_bump: 0,
+            program_authority: None,
+            min_transfer_amount: 0,
+            tier0_max: 0,
+            tier1_max: 0,
+            record_removals: false,
+            required_mint: None,
+            cooldown_slots: 0,
+            recent_removals: Vec::new(),
+            bypass_sender: None,
+            match_mode: MatchMode::Owner,
+            allowed_owner_programs: Vec::new(),
+            require_existing_destination: false,
+            prevent_empty: false,
+            require_confirmation: false,
+            pending_additions: Vec::new(),
+            allow_zero_amount: false,
+            per_epoch_quota: 0,
+            max_destinations_per_window: 0,
+            fan_out_window_seconds: 0,
+            verbose_logging: false,
+            authority_pda: None,
+            attestation_program: None,
+            attestation_expiry_offset: None,
+            allow_burn: false,
+            meta_list_authority: None,
+            same_owner_only: false,
+            fee_numerator: 0,
+            fee_denominator: 0,
+        }
+    }
+
+    fn is_error(result: Result<()>, expected: TransferError) -> bool {
+        result.is_err_and(|error| error == expected.into())
+    }
+
+    fn is_not_currently_transferring(result: Result<()>) -> bool {
+        is_error(result, TransferError::IsNotCurrentlyTransferring)
+    }
+
+    #[test]
+    fn transferring_account_passes() {
+        let mut data = token_account_data(Some(true));
+        assert!(check_is_transferring_data(&mut data).is_ok());
+    }
+
+    #[test]
+    fn not_transferring_account_is_rejected() {
+        let mut data = token_account_data(Some(false));
+        assert!(is_not_currently_transferring(check_is_transferring_data(&mut data)));
+    }
+
+    #[test]
+    fn account_without_extension_is_rejected() {
+        let mut data = token_account_data(None);
+        assert_eq!(data.len(), Account::LEN);
+        assert!(is_not_currently_transferring(check_is_transferring_data(&mut data)));
+    }
+
+    #[test]
+    fn self_transfer_allowed_by_default() {
+        let white_list = white_list();
+        let account = Pubkey::new_unique();
+        assert!(white_list.check_self_transfer(&account, &account).is_ok());
+    }
+
+    #[test]
+    fn self_transfer_rejected_when_blocked() {
+        let mut white_list = white_list();
+        white_list.block_self_transfer = true;
+        let account = Pubkey::new_unique();
+        assert!(is_error(white_list.check_self_transfer(&account, &account), TransferError::SelfTransferNotAllowed));
+        assert!(white_list.check_self_transfer(&account, &Pubkey::new_unique()).is_ok());
+    }
+
+    // Cây Merkle 4 lá, trả về root và bằng chứng của từng lá
+    fn merkle_tree(owners: &[Pubkey; 4]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let pair = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b { hashv(&[&a, &b]).to_bytes() } else { hashv(&[&b, &a]).to_bytes() }
+        };
+        let leaves: Vec<[u8; 32]> = owners.iter().map(merkle_leaf).collect();
+        let left = pair(leaves[0], leaves[1]);
+        let right = pair(leaves[2], leaves[3]);
+        let proofs = vec![
+            vec![leaves[1], right],
+            vec![leaves[0], right],
+            vec![leaves[3], left],
+            vec![leaves[2], left]
+        ];
+        (pair(left, right), proofs)
+    }
+
+    #[test]
+    fn merkle_proof_accepts_every_leaf() {
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let (root, proofs) = merkle_tree(&owners);
+        for (owner, proof) in owners.iter().zip(proofs.iter()) {
+            assert!(verify_merkle_proof(merkle_leaf(owner), proof, &root));
+        }
+    }
+
+    #[test]
+    fn merkle_proof_rejects_other_owner() {
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let (root, proofs) = merkle_tree(&owners);
+        assert!(!verify_merkle_proof(merkle_leaf(&Pubkey::new_unique()), &proofs[0], &root));
+        assert!(!verify_merkle_proof(merkle_leaf(&owners[0]), &proofs[2], &root));
+    }
+
+    #[test]
+    fn rate_counter_limits_within_window_and_resets() {
+        let mut counter = RateCounter { owner: Pubkey::new_unique(), window_start: 100, count: 0 };
+        assert!(counter.record(100, 2, 60));
+        assert!(counter.record(130, 2, 60));
+        assert!(!counter.record(159, 2, 60));
+        // Khoảng thời gian mới bắt đầu tại 160
+        assert!(counter.record(160, 2, 60));
+        assert_eq!(counter.window_start, 160);
+        assert_eq!(counter.count, 1);
+    }
+
+    #[test]
+    fn epoch_quota_limits_within_epoch_and_resets() {
+        let mut quota = EpochQuota { owner: Pubkey::new_unique(), epoch: 5, moved: 0 };
+        assert!(quota.record(5, 60, 100));
+        assert!(quota.record(5, 40, 100));
+        assert!(!quota.record(5, 1, 100));
+        // Epoch mới đặt lại tổng số lượng
+        assert!(quota.record(6, 100, 100));
+        assert_eq!(quota.epoch, 6);
+        assert_eq!(quota.moved, 100);
+    }
+
+    #[test]
+    fn fan_out_counter_counts_new_destinations() {
+        let mut counter = FanOutCounter { owner: Pubkey::new_unique(), window_start: 100, count: 0, recent_destinations: Vec::new() };
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        assert!(counter.record(first, 100, 2, 60));
+        assert!(counter.record(second, 110, 2, 60));
+        // Gửi lại đến địa chỉ đã gặp không được tính là địa chỉ mới
+        assert!(counter.record(first, 120, 2, 60));
+        assert!(!counter.record(Pubkey::new_unique(), 130, 2, 60));
+        // Khoảng thời gian mới bắt đầu tại 160
+        assert!(counter.record(Pubkey::new_unique(), 160, 2, 60));
+        assert_eq!(counter.count, 1);
+        assert_eq!(counter.recent_destinations.len(), 1);
+    }
+
+    #[test]
+    fn transfer_amount_band() {
+        let mut white_list = white_list();
+        let destination = Pubkey::new_unique();
+        white_list.mode = WhitelistMode::DenyList;
+        white_list.min_transfer_amount = 10;
+        white_list.max_transfer_amount = Some(100);
+        let check = |white_list: &WhiteList, amount| white_list.check_policy(&[], &destination, None, amount, 0);
+        assert!(is_error(check(&white_list, 9), TransferError::TransferAmountTooSmall));
+        assert!(check(&white_list, 10).is_ok());
+        assert!(check(&white_list, 100).is_ok());
+        assert!(is_error(check(&white_list, 101), TransferError::TransferAmountExceeded));
+        white_list.min_transfer_amount = 0;
+        assert!(check(&white_list, 0).is_ok());
+    }
+
+    #[test]
+    fn ensure_room_rejects_overfill() {
+        let mut white_list = white_list();
+        let data_len = WhiteList::space_for(2);
+        let capacity = white_list.capacity(data_len);
+        assert_eq!(capacity, 2);
+
+        for _ in 0..2 {
+            let entry = WhiteListEntry { account: Pubkey::new_unique(), added_at: 0, expires_at: None, tier: 0, changed_slot: 0, suspended: false };
+            white_list.push_entry(entry, capacity).unwrap();
+        }
+        let entry = WhiteListEntry { account: Pubkey::new_unique(), added_at: 0, expires_at: None, tier: 0, changed_slot: 0, suspended: false };
+        assert!(is_error(white_list.push_entry(entry, capacity), TransferError::WhitelistFull));
+        assert_eq!(white_list.white_list.len(), 2);
+
+        // Số lượng cực lớn không được gây tràn số
+        assert!(is_error(white_list.ensure_room(usize::MAX, usize::MAX), TransferError::WhitelistFull));
+        assert!(is_error(white_list.ensure_room(1, capacity), TransferError::WhitelistFull));
+        assert!(white_list.ensure_room(0, capacity).is_ok());
+    }
+
+    #[test]
+    fn tier_limit_applies_to_destination_tier() {
+        let mut white_list = white_list();
+        let trusted = Pubkey::new_unique();
+        let probationary = Pubkey::new_unique();
+        white_list.white_list.push(WhiteListEntry { account: trusted, added_at: 0, expires_at: None, tier: 0, changed_slot: 0, suspended: false });
+        white_list.white_list.push(WhiteListEntry { account: probationary, added_at: 0, expires_at: None, tier: 1, changed_slot: 0, suspended: false });
+        white_list.tier1_max = 100;
+
+        assert!(white_list.check_policy(&[], &trusted, None, 1_000, 0).is_ok());
+        assert!(white_list.check_policy(&[], &probationary, None, 100, 0).is_ok());
+        assert!(is_error(white_list.check_policy(&[], &probationary, None, 101, 0), TransferError::TierLimitExceeded));
+
+        white_list.tier0_max = 500;
+        assert!(is_error(white_list.check_policy(&[], &trusted, None, 1_000, 0), TransferError::TierLimitExceeded));
+    }
+
+    #[test]
+    fn page_clamps_to_bounds() {
+        let mut white_list = white_list();
+        let accounts: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        for account in &accounts {
+            white_list.white_list.push(WhiteListEntry { account: *account, added_at: 0, expires_at: None, tier: 0, changed_slot: 0, suspended: false });
+        }
+
+        assert_eq!(white_list.page(0, 2), accounts[0..2]);
+        assert_eq!(white_list.page(3, 10), accounts[3..5]);
+        assert!(white_list.page(5, 10).is_empty());
+        assert!(white_list.page(u32::MAX, u32::MAX).is_empty());
+        assert!(white_list.page(0, 0).is_empty());
+    }
+
+    #[test]
+    fn removal_audit_fills_and_prunes() {
+        let mut audit = RemovalAudit { bump: 0, records: Vec::new() };
+        let capacity = RemovalAudit::capacity_for(RemovalAudit::space_for(3));
+        assert_eq!(capacity, 3);
+
+        let first = [Pubkey::new_unique(), Pubkey::new_unique()];
+        audit.record(&first, 100, capacity).unwrap();
+        let second = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert!(is_error(audit.record(&second, 200, capacity), TransferError::AuditLogFull));
+        assert_eq!(audit.records.len(), 2);
+
+        assert_eq!(audit.prune(150), 2);
+        audit.record(&second, 200, capacity).unwrap();
+        assert_eq!(audit.page(0, 10), vec![
+            RemovalRecord { account: second[0], removed_at: 200 },
+            RemovalRecord { account: second[1], removed_at: 200 },
+        ]);
+    }
+
+    #[test]
+    fn uninitialized_white_list_is_rejected() {
+        let mut white_list = white_list();
+        assert!(white_list.check_initialized(&crate::ID).is_ok());
+        assert!(is_error(white_list.check_initialized(&Pubkey::new_unique()), TransferError::UninitializedWhitelist));
+
+        white_list.authority = Pubkey::default();
+        assert!(is_error(white_list.check_initialized(&crate::ID), TransferError::UninitializedWhitelist));
+    }
+
+    #[test]
+    fn cooldown_blocks_quick_changes() {
+        let mut white_list = white_list();
+        let account = Pubkey::new_unique();
+        white_list.cooldown_slots = 10;
+        white_list.white_list.push(WhiteListEntry { account, added_at: 0, expires_at: None, tier: 0, changed_slot: 100, suspended: false });
+
+        assert!(is_error(white_list.check_cooldown(&account, 109), TransferError::AddressCooldown));
+        assert!(white_list.check_cooldown(&account, 110).is_ok());
+
+        white_list.white_list.clear();
+        white_list.note_removal(account, 110);
+        assert!(is_error(white_list.check_cooldown(&account, 119), TransferError::AddressCooldown));
+        assert!(white_list.check_cooldown(&account, 120).is_ok());
+        assert!(white_list.check_cooldown(&Pubkey::new_unique(), 110).is_ok());
+
+        // Bản ghi đã hết thời gian chờ được dọn, số bản ghi không vượt quá giới hạn
+        for slot in 0..(WhiteList::MAX_RECENT_REMOVALS as u64 + 2) {
+            white_list.note_removal(Pubkey::new_unique(), 200 + slot);
+        }
+        assert_eq!(white_list.recent_removals.len(), WhiteList::MAX_RECENT_REMOVALS);
+    }
+
+    #[test]
+    fn bypass_sender_skips_destination_check() {
+        let mut white_list = white_list();
+        let treasury = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        assert!(is_error(white_list.check_policy(&[treasury], &recipient, None, 1, 0), TransferError::NotWhitelisted));
+
+        white_list.bypass_sender = Some(treasury);
+        assert!(white_list.check_policy(&[treasury], &recipient, None, 1, 0).is_ok());
+        assert!(is_error(white_list.check_policy(&[Pubkey::new_unique()], &recipient, None, 1, 0), TransferError::NotWhitelisted));
+
+        // Các chính sách khác vẫn được áp dụng
+        white_list.paused = true;
+        assert!(is_error(white_list.check_policy(&[treasury], &recipient, None, 1, 0), TransferError::TransfersPaused));
+    }
+
+    fn entry(account: Pubkey) -> WhiteListEntry {
+        WhiteListEntry { account, added_at: 0, expires_at: None, tier: 0, changed_slot: 0, suspended: false }
+    }
+
+    #[test]
+    fn added_account_is_member() {
+        let mut white_list = white_list();
+        let account = Pubkey::new_unique();
+        white_list.add_entry(entry(account), 10).unwrap();
+        assert!(white_list.contains(&account));
+        assert!(!white_list.contains(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn duplicate_add_is_rejected() {
+        let mut white_list = white_list();
+        let account = Pubkey::new_unique();
+        white_list.add_entry(entry(account), 10).unwrap();
+        assert!(is_error(white_list.add_entry(entry(account), 10), TransferError::AlreadyWhitelisted));
+        assert_eq!(white_list.white_list.len(), 1);
+    }
+
+    #[test]
+    fn removed_account_is_absent() {
+        let mut white_list = white_list();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        white_list.add_entry(entry(first), 10).unwrap();
+        white_list.add_entry(entry(second), 10).unwrap();
+
+        white_list.remove_account(&first, 0).unwrap();
+        assert!(!white_list.contains(&first));
+        assert_eq!(white_list.white_list, vec![entry(second)]);
+    }
+
+    #[test]
+    fn removing_missing_account_fails() {
+        let mut white_list = white_list();
+        white_list.add_entry(entry(Pubkey::new_unique()), 10).unwrap();
+        assert!(is_error(white_list.remove_account(&Pubkey::new_unique(), 0), TransferError::AccountNotFound));
+        assert_eq!(white_list.white_list.len(), 1);
+    }
+
+    #[test]
+    fn only_managers_are_authorized() {
+        let mut white_list = white_list();
+        let admin = Pubkey::new_unique();
+        white_list.admins.push(admin);
+
+        let mint = Pubkey::new_unique();
+
+        assert!(is_authorized_manager(&white_list, &mint, &white_list.authority));
+        assert!(is_authorized_manager(&white_list, &mint, &admin));
+        assert!(!is_authorized_manager(&white_list, &mint, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn program_authority_must_sign_with_its_pda() {
+        let mut white_list = white_list();
+        let mint = Pubkey::new_unique();
+        let governance = Pubkey::new_unique();
+        let signer = program_authority_signer(&governance, &mint);
+        // Chưa ủy quyền cho program nào
+        assert!(!is_authorized_manager(&white_list, &mint, &signer));
+
+        white_list.program_authority = Some(governance);
+        assert!(is_authorized_manager(&white_list, &mint, &signer));
+        // PDA của program khác, PDA cho mint khác hoặc chính program id đều không được chấp nhận
+        assert!(!is_authorized_manager(&white_list, &mint, &program_authority_signer(&Pubkey::new_unique(), &mint)));
+        assert!(!is_authorized_manager(&white_list, &mint, &program_authority_signer(&governance, &Pubkey::new_unique())));
+        assert!(!is_authorized_manager(&white_list, &mint, &governance));
+    }
+
+    #[test]
+    fn authority_target_must_be_owned_by_program() {
+        let multisig_program = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = [];
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &multisig_program, false, 0);
+
+        assert!(check_authority_target(&account, &key, &multisig_program).is_ok());
+        assert!(is_error(
+            check_authority_target(&account, &key, &Pubkey::new_unique()),
+            TransferError::InvalidAuthorityTarget
+        ));
+        assert!(is_error(
+            check_authority_target(&account, &Pubkey::new_unique(), &multisig_program),
+            TransferError::InvalidAuthorityTarget
+        ));
+    }
+
+    #[test]
+    fn match_mode_selects_destination_key() {
+        let mut white_list = white_list();
+        let token_account = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        assert_eq!(white_list.destination_key(&token_account, &owner), owner);
+
+        white_list.match_mode = MatchMode::TokenAccount;
+        assert_eq!(white_list.destination_key(&token_account, &owner), token_account);
+    }
+
+    #[test]
+    fn replace_entries_keeps_existing_entries() {
+        let mut white_list = white_list();
+        let kept = Pubkey::new_unique();
+        let dropped = Pubkey::new_unique();
+        let added = Pubkey::new_unique();
+        white_list.white_list.push(WhiteListEntry { account: kept, added_at: 1, expires_at: None, tier: 1, changed_slot: 0, suspended: false });
+        white_list.white_list.push(entry(dropped));
+
+        let removed = white_list.replace_entries(&[kept, added, added], 50, 7, 10).unwrap();
+        assert_eq!(removed, vec![dropped]);
+        assert_eq!(white_list.white_list, vec![
+            WhiteListEntry { account: kept, added_at: 1, expires_at: None, tier: 1, changed_slot: 0, suspended: false },
+            WhiteListEntry { account: added, added_at: 50, expires_at: None, tier: 0, changed_slot: 7, suspended: false },
+        ]);
+
+        let accounts: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        assert!(is_error(white_list.replace_entries(&accounts, 0, 0, 2).map(|_| ()), TransferError::WhitelistFull));
+        assert_eq!(white_list.white_list.len(), 2);
+    }
+
+    #[test]
+    fn entries_stay_sorted() {
+        let mut white_list = white_list();
+        let mut accounts: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+        accounts.reverse();
+        for account in &accounts {
+            white_list.add_entry(entry(*account), 10).unwrap();
+            assert!(white_list.is_sorted());
+        }
+        for account in accounts.iter().step_by(3) {
+            white_list.remove_account(account, 0).unwrap();
+            assert!(white_list.is_sorted());
+        }
+        white_list.add_entry(entry(accounts[0]), 10).unwrap();
+        assert!(white_list.is_sorted());
+        assert!(accounts.iter().skip(1).step_by(3).all(|account| white_list.contains(account)));
+        assert!(white_list.config_violations(WhiteList::space_for(10)).is_empty());
+
+        white_list.white_list.reverse();
+        assert!(white_list.config_violations(WhiteList::space_for(10)).contains(&ConfigViolation::UnsortedEntries));
+        white_list.sort_entries();
+        assert!(white_list.is_sorted());
+    }
+
+    #[test]
+    fn whole_tokens_scale_by_decimals() {
+        assert_eq!(whole_tokens_to_base_units(100, 0).unwrap(), 100);
+        assert_eq!(whole_tokens_to_base_units(100, 9).unwrap(), 100_000_000_000);
+        assert!(is_error(whole_tokens_to_base_units(1, 20).map(|_| ()), TransferError::AmountOverflow));
+        assert!(is_error(whole_tokens_to_base_units(u64::MAX, 1).map(|_| ()), TransferError::AmountOverflow));
+    }
+
+    #[test]
+    fn allowed_owner_program_vault_can_receive() {
+        let mut white_list = white_list();
+        let amm = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        assert!(is_error(white_list.check_policy(&[], &vault, Some(amm), 1, 0), TransferError::NotWhitelisted));
+
+        white_list.allowed_owner_programs.push(amm);
+        assert!(white_list.check_policy(&[], &vault, Some(amm), 1, 0).is_ok());
+        assert!(is_error(white_list.check_policy(&[], &vault, None, 1, 0), TransferError::NotWhitelisted));
+        assert!(is_error(
+            white_list.check_policy(&[], &vault, Some(Pubkey::new_unique()), 1, 0),
+            TransferError::NotWhitelisted
+        ));
+    }
+
+    #[test]
+    fn uninitialized_meta_list_is_rejected() {
+        let key = Pubkey::new_unique();
+        let system_program = Pubkey::default();
+        let mut lamports = 0;
+        let mut empty = [];
+        let meta_list = AccountInfo::new(&key, false, false, &mut lamports, &mut empty, &system_program, false, 0);
+        assert!(is_error(check_meta_list_initialized(&meta_list), TransferError::MetaListNotInitialized));
+
+        let mut lamports = 0;
+        let mut data = [0u8; 16];
+        let meta_list = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert!(check_meta_list_initialized(&meta_list).is_ok());
+    }
+
+    #[test]
+    fn grown_len_respects_realloc_limit() {
+        let data_len = WhiteList::space_for(10);
+        assert_eq!(WhiteList::grown_len(data_len, 5).unwrap(), WhiteList::space_for(15));
+        assert_eq!(WhiteList::grown_len(data_len, 0).unwrap(), data_len);
+
+        let max_entries = (MAX_PERMITTED_DATA_INCREASE / WhiteList::ENTRY_LEN) as u16;
+        assert!(WhiteList::grown_len(data_len, max_entries).is_ok());
+        assert!(is_error(WhiteList::grown_len(data_len, max_entries + 1).map(|_| ()), TransferError::ReallocFailed));
+    }
+
+    #[test]
+    fn require_existing_destination_rejects_new_accounts() {
+        let mut white_list = white_list();
+        let destination = Pubkey::new_unique();
+        assert!(white_list.check_destination_exists(&destination, 10, 10).is_ok());
+
+        white_list.require_existing_destination = true;
+        assert!(is_error(white_list.check_destination_exists(&destination, 10, 10), TransferError::DestinationMustPreExist));
+        assert!(white_list.check_destination_exists(&destination, 15, 10).is_ok());
+
+        // Account token đích có trong whitelist được chấp nhận dù chưa có số dư trước đó
+        white_list.add_entry(entry(destination), 10).unwrap();
+        assert!(white_list.check_destination_exists(&destination, 10, 10).is_ok());
+    }
+
+    #[test]
+    fn config_snapshot_reflects_white_list() {
+        let mut white_list = white_list();
+        white_list.paused = true;
+        white_list.max_transfer_amount = Some(500);
+        white_list.add_entry(entry(Pubkey::new_unique()), 10).unwrap();
+
+        let config = white_list.config(WhiteList::space_for(10));
+        assert_eq!(config.authority, white_list.authority);
+        assert!(config.paused);
+        assert_eq!(config.length, 1);
+        assert_eq!(config.capacity, 10);
+        assert_eq!(config.max_transfer_amount, Some(500));
+        // Ảnh chụp phải vừa return data kể cả khi các danh sách đầy
+        white_list.admins = vec![Pubkey::new_unique(); WhiteList::MAX_ADMINS];
+        white_list.allowed_owner_programs = vec![Pubkey::new_unique(); WhiteList::MAX_OWNER_PROGRAMS];
+        white_list.authority_pda = Some(AuthorityPda {
+            program_id: Pubkey::new_unique(),
+            seeds: vec![vec![0; AuthorityPda::MAX_SEED_LEN]; AuthorityPda::MAX_SEEDS],
+            bump: 255,
+        });
+        assert!(white_list.config(WhiteList::space_for(10)).try_to_vec().unwrap().len() <= 1024);
+    }
+
+    #[test]
+    fn prevent_empty_blocks_removing_last_entry() {
+        let mut white_list = white_list();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        white_list.add_entry(entry(first), 10).unwrap();
+        white_list.add_entry(entry(second), 10).unwrap();
+        white_list.prevent_empty = true;
+
+        white_list.remove_account(&first, 0).unwrap();
+        assert!(white_list.check_not_empty().is_ok());
+        white_list.remove_account(&second, 0).unwrap();
+        assert!(is_error(white_list.check_not_empty(), TransferError::CannotEmptyWhitelist));
+
+        white_list.prevent_empty = false;
+        assert!(white_list.check_not_empty().is_ok());
+    }
+
+    #[test]
+    fn pending_addition_requires_second_signer() {
+        let mut white_list = white_list();
+        let account = Pubkey::new_unique();
+        let proposer = Pubkey::new_unique();
+        let pending = PendingAddition { account, proposer, expires_at: None };
+        white_list.propose_addition(pending).unwrap();
+        assert!(is_error(white_list.propose_addition(pending), TransferError::AdditionAlreadyPending));
+        assert!(!white_list.contains(&account));
+
+        assert!(is_error(white_list.take_pending_addition(&account, &proposer).map(|_| ()), TransferError::SameSignerNotAllowed));
+        assert_eq!(white_list.take_pending_addition(&account, &Pubkey::new_unique()).unwrap(), pending);
+        assert!(white_list.pending_additions.is_empty());
+        assert!(is_error(
+            white_list.take_pending_addition(&account, &Pubkey::new_unique()).map(|_| ()),
+            TransferError::PendingAdditionNotFound
+        ));
+    }
+
+    #[test]
+    fn pending_additions_are_bounded() {
+        let mut white_list = white_list();
+        let proposer = Pubkey::new_unique();
+        for _ in 0..WhiteList::MAX_PENDING_ADDITIONS {
+            white_list.propose_addition(PendingAddition { account: Pubkey::new_unique(), proposer, expires_at: None }).unwrap();
+        }
+        assert!(is_error(
+            white_list.propose_addition(PendingAddition { account: Pubkey::new_unique(), proposer, expires_at: None }),
+            TransferError::TooManyPendingAdditions
+        ));
+    }
+
+    #[test]
+    fn snapshot_slot_must_be_recent() {
+        assert!(WhitelistSnapshot::check_slot(100, 100).is_ok());
+        assert!(WhitelistSnapshot::check_slot(100, 100 + WhitelistSnapshot::MAX_SLOT_AGE).is_ok());
+        assert!(is_error(WhitelistSnapshot::check_slot(101, 100), TransferError::InvalidSnapshotSlot));
+        assert!(is_error(
+            WhitelistSnapshot::check_slot(100, 101 + WhitelistSnapshot::MAX_SLOT_AGE),
+            TransferError::InvalidSnapshotSlot
+        ));
+        // Ảnh chụp lớn nhất vẫn được tạo được qua CPI
+        assert!(WhitelistSnapshot::space_for(WhitelistSnapshot::MAX_ACCOUNTS) <= MAX_PERMITTED_DATA_INCREASE);
+    }
+
+    #[test]
+    fn zero_amount_exemption_is_opt_in() {
+        let mut white_list = white_list();
+        assert!(!white_list.is_zero_amount_exempt(0));
+
+        white_list.allow_zero_amount = true;
+        assert!(white_list.is_zero_amount_exempt(0));
+        assert!(!white_list.is_zero_amount_exempt(1));
+
+        white_list.paused = true;
+        assert!(!white_list.is_zero_amount_exempt(0));
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        // Mã lỗi là giao diện với client, các biến thể hiện có không được đổi vị trí
+        assert_eq!(u32::from(TransferError::IsNotCurrentlyTransferring), 6000);
+        assert_eq!(u32::from(TransferError::AccountNotFound), 6001);
+        assert_eq!(u32::from(TransferError::NotWhitelisted), 6005);
+        assert_eq!(u32::from(TransferError::Unauthorized), 6006);
+        assert_eq!(u32::from(TransferError::WhitelistFull), 6008);
+        assert_eq!(u32::from(TransferError::AlreadyWhitelisted), 6009);
+    }
+
+    #[test]
+    fn rent_surplus_keeps_account_rent_exempt() {
+        let rent = Rent::default();
+        let data_len = WhiteList::space_for(10);
+        let minimum = rent.minimum_balance(data_len);
+        assert_eq!(rent_surplus(&rent, minimum, data_len), 0);
+        assert_eq!(rent_surplus(&rent, minimum - 1, data_len), 0);
+        // Sau khi giảm kích thước, phần chênh lệch được thu hồi
+        let before_shrink = rent.minimum_balance(WhiteList::space_for(20));
+        assert_eq!(rent_surplus(&rent, before_shrink, data_len), before_shrink - minimum);
+    }
+
+    #[test]
+    fn rent_top_up_reaches_minimum_for_new_size() {
+        let rent = Rent::default();
+        let old_len = WhiteList::space_for(10);
+        let new_len = WhiteList::grown_len(old_len, 10).unwrap();
+        let lamports = rent.minimum_balance(old_len);
+        let top_up = rent_top_up(&rent, lamports, u64::MAX, new_len).unwrap();
+        // Số dư sau khi tăng kích thước đạt ít nhất mức miễn rent của kích thước mới
+        assert!(rent.is_exempt(lamports + top_up, new_len));
+        assert!(!rent.is_exempt(lamports + top_up - 1, new_len));
+        // Account đã đủ lamports thì không cần nạp thêm
+        assert_eq!(rent_top_up(&rent, rent.minimum_balance(new_len), 0, new_len).unwrap(), 0);
+        // payer không đủ lamports
+        assert!(is_error(rent_top_up(&rent, lamports, top_up - 1, new_len).map(|_| ()), TransferError::InsufficientRentTopUp));
+    }
+
+    #[test]
+    fn fast_lookup_finds_sorted_entries() {
+        let mut accounts: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        accounts.sort();
+        let list: Vec<WhiteListEntry> = accounts.iter().map(|account| entry(*account)).collect();
+        assert!(accounts.iter().all(|account| is_whitelisted_fast(&list, account)));
+        assert!(!is_whitelisted_fast(&list, &Pubkey::new_unique()));
+        assert!(!is_whitelisted_fast(&[], &accounts[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "white list entries must be sorted by account")]
+    fn fast_lookup_asserts_sorted_input() {
+        let mut accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+        accounts.sort();
+        let list = [entry(accounts[1]), entry(accounts[0])];
+        is_whitelisted_fast(&list, &accounts[0]);
+    }
+
+    #[test]
+    fn remove_admin_keeps_someone_in_control() {
+        let mut white_list = white_list();
+        let admin = Pubkey::new_unique();
+        white_list.admins.push(admin);
+
+        // authority không bao giờ bị xóa qua remove_admin, chỉ authority được xóa admin
+        let authority = white_list.authority;
+        assert!(is_error(white_list.remove_admin(&authority, &authority), TransferError::CannotRemoveLastAdmin));
+        assert!(is_error(white_list.remove_admin(&authority, &Pubkey::new_unique()), TransferError::AdminNotFound));
+        assert!(is_error(white_list.remove_admin(&admin, &admin), TransferError::Unauthorized));
+
+        // authority được chuyển cho PDA của program quản trị như set_authority_pda,
+        // PDA ký qua CPI nhưng không được xóa admin cuối cùng
+        let (address, authority_pda) = AuthorityPda::derive(Pubkey::new_unique(), vec![b"governance".to_vec()]).unwrap();
+        white_list.assign_authority_pda(address, authority_pda);
+        assert!(white_list.authority_is_pda());
+        assert!(is_error(white_list.remove_admin(&address, &admin), TransferError::CannotRemoveLastAdmin));
+        assert_eq!(white_list.admins, vec![admin]);
+
+        // Còn hai admin thì xóa được một
+        let other = Pubkey::new_unique();
+        white_list.admins.push(other);
+        white_list.remove_admin(&address, &admin).unwrap();
+        assert!(is_error(white_list.remove_admin(&address, &other), TransferError::CannotRemoveLastAdmin));
+
+        // authority là địa chỉ thông thường (sau accept_authority) thì admin cuối cùng có thể bị xóa
+        let authority = Pubkey::new_unique();
+        white_list.authority = authority;
+        white_list.authority_pda = None;
+        white_list.remove_admin(&authority, &other).unwrap();
+        assert!(white_list.admins.is_empty());
+    }
+
+    #[test]
+    fn update_meta_list_uses_interface_discriminator() {
+        assert_eq!(
+            crate::instruction::UpdateExtraAccountMetaList::DISCRIMINATOR,
+            UpdateExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE
+        );
+        // Hai instruction bắt buộc của interface giữ nguyên discriminator sau khi bỏ #[interface]
+        assert_eq!(
+            crate::instruction::InitializeExtraAccountMetaList::DISCRIMINATOR,
+            InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(crate::instruction::TransferHook::DISCRIMINATOR, ExecuteInstruction::SPL_DISCRIMINATOR_SLICE);
+    }
+
+    #[test]
+    fn remove_from_empty_whitelist_keeps_account_not_found() {
+        let mut white_list = white_list();
+        assert!(white_list.white_list.is_empty());
+        assert!(is_error(white_list.remove_account(&Pubkey::new_unique(), 0), TransferError::AccountNotFound));
+    }
+
+    #[test]
+    fn require_member_rejects_missing_and_expired_entries() {
+        let mut white_list = white_list();
+        let member = Pubkey::new_unique();
+        let expiring = Pubkey::new_unique();
+        white_list.add_entry(entry(member), 10).unwrap();
+        white_list.add_entry(WhiteListEntry { expires_at: Some(100), ..entry(expiring) }, 10).unwrap();
+
+        assert!(white_list.require_member(&member, 200).is_ok());
+        assert!(white_list.require_member(&expiring, 99).is_ok());
+        assert!(is_error(white_list.require_member(&expiring, 100), TransferError::NotWhitelisted));
+        assert!(is_error(white_list.require_member(&Pubkey::new_unique(), 0), TransferError::NotWhitelisted));
+    }
+
+    #[test]
+    fn sorting_unsorted_entries_restores_binary_search() {
+        let mut white_list = white_list();
+        let accounts: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        // Thứ tự tùy ý kèm địa chỉ bị lặp, như account tạo trước khi whitelist được sắp xếp
+        for account in accounts.iter().rev().chain(accounts.iter().step_by(2)) {
+            white_list.white_list.push(entry(*account));
+        }
+        assert!(!white_list.is_sorted());
+
+        assert_eq!(white_list.sort_entries(), 3);
+        assert!(white_list.is_sorted());
+        assert_eq!(white_list.white_list.len(), accounts.len());
+        for account in &accounts {
+            let index = white_list.white_list.binary_search_by_key(account, |entry| entry.account).unwrap();
+            assert_eq!(white_list.position(account), Some(index));
+        }
+        assert!(white_list.white_list.binary_search_by_key(&Pubkey::new_unique(), |entry| entry.account).is_err());
+        assert_eq!(white_list.sort_entries(), 0);
+    }
+
+    #[test]
+    fn authority_pda_derivation_round_trips() {
+        let program_id = Pubkey::new_unique();
+        let seeds = vec![b"governance".to_vec(), Pubkey::new_unique().to_bytes().to_vec()];
+        let seed_slices: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let (expected, bump) = Pubkey::find_program_address(&seed_slices, &program_id);
+
+        let (address, authority_pda) = AuthorityPda::derive(program_id, seeds.clone()).unwrap();
+        assert_eq!(address, expected);
+        assert_eq!(authority_pda, AuthorityPda { program_id, seeds, bump });
+        assert_eq!(authority_pda.address(), Some(expected));
+
+        // Giá trị lớn nhất phải vừa phần không gian đã dành sẵn
+        let (_, largest) = AuthorityPda::derive(program_id, vec![vec![7; AuthorityPda::MAX_SEED_LEN]; AuthorityPda::MAX_SEEDS]).unwrap();
+        assert_eq!(Some(largest).try_to_vec().unwrap().len(), AuthorityPda::MAX_LEN);
+
+        let too_many = vec![Vec::new(); AuthorityPda::MAX_SEEDS + 1];
+        assert!(matches!(AuthorityPda::derive(program_id, too_many), Err(error) if error == TransferError::InvalidAuthoritySeeds.into()));
+        let too_long = vec![vec![0; AuthorityPda::MAX_SEED_LEN + 1]];
+        assert!(matches!(AuthorityPda::derive(program_id, too_long), Err(error) if error == TransferError::InvalidAuthoritySeeds.into()));
+    }
+
+    #[test]
+    fn token_accounts_must_share_the_transfer_mint() {
+        let mint = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+        assert!(check_token_mints(&mint, &mint, &mint).is_ok());
+        assert!(is_error(check_token_mints(&mint, &mint, &other_mint), TransferError::MintMismatch));
+        assert!(is_error(check_token_mints(&mint, &other_mint, &mint), TransferError::MintMismatch));
+    }
+
+    #[test]
+    fn attestation_must_be_valid_and_unexpired() {
+        let mut white_list = white_list();
+        let attestation_program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let (key, _) = Pubkey::find_program_address(&[ATTESTATION_SEED, owner.as_ref()], &attestation_program);
+        let mut lamports = 1;
+        let mut data = [0u8; 16];
+        data[8..].copy_from_slice(&100i64.to_le_bytes());
+        let attestation = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &attestation_program, false, 0);
+
+        // Chưa cấu hình program cấp attestation
+        assert!(is_error(white_list.check_attestation(&attestation, &owner, 0), TransferError::AttestationRequired));
+        white_list.attestation_program = Some(attestation_program);
+        assert!(white_list.check_attestation(&attestation, &owner, 200).is_ok());
+        // Attestation của một ví khác không được chấp nhận
+        assert!(is_error(white_list.check_attestation(&attestation, &Pubkey::new_unique(), 0), TransferError::AttestationRequired));
+
+        white_list.attestation_expiry_offset = Some(8);
+        assert!(white_list.check_attestation(&attestation, &owner, 99).is_ok());
+        assert!(is_error(white_list.check_attestation(&attestation, &owner, 100), TransferError::AttestationExpired));
+        white_list.attestation_expiry_offset = Some(12);
+        assert!(is_error(white_list.check_attestation(&attestation, &owner, 0), TransferError::AttestationRequired));
+    }
+
+    #[test]
+    fn extra_account_meta_count_ignores_attestation_program() {
+        let placeholder = InitializeExtraAccountMetaList::extra_account_metas(None).unwrap();
+        let configured = InitializeExtraAccountMetaList::extra_account_metas(Some(Pubkey::new_unique())).unwrap();
+        assert_eq!(placeholder.len(), configured.len());
+        assert_ne!(placeholder[14], configured[14]);
+    }
+
+    #[test]
+    fn client_helpers_match_on_chain_seeds() {
+        let mint = Pubkey::new_unique();
+        let (white_list, _) = client::whitelist_pda(&crate::ID, &mint);
+        let (meta_list, meta_list_bump) = client::extra_account_metas_pda(&crate::ID, &mint);
+        assert_eq!(meta_list_bump, WhiteList::find_meta_list_bump(&mint));
+
+        // Seed của whitelist trong ExtraAccountMetaList (index 5) phải trùng với client
+        let metas = InitializeExtraAccountMetaList::extra_account_metas(None).unwrap();
+        let expected = ExtraAccountMeta::new_with_seeds(
+            &[Seed::Literal { bytes: WHITE_LIST_SEED.to_vec() }, Seed::AccountKey { index: 1 }],
+            false,
+            true
+        ).unwrap();
+        assert_eq!(metas[0], expected);
+
+        let signer = Pubkey::new_unique();
+        let new_account = Pubkey::new_unique();
+        let ix = client::add_to_whitelist_ix(&crate::ID, &mint, &new_account, &signer);
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(keys, vec![mint, new_account, white_list, signer]);
+        assert!(ix.accounts[3].is_signer);
+        assert_eq!(&ix.data[..8], crate::instruction::AddToWhitelist::DISCRIMINATOR);
+
+        let ix = client::initialize_extra_account_meta_list_ix(&crate::ID, &signer, &mint, 10);
+        assert_eq!(ix.accounts[1].pubkey, meta_list);
+        assert_eq!(ix.accounts[4].pubkey, white_list);
+        // Seed của membership_config trong ExtraAccountMetaList (index 6) cũng trùng với client
+        let expected = ExtraAccountMeta::new_with_seeds(
+            &[Seed::Literal { bytes: MEMBERSHIP_SEED.to_vec() }, Seed::AccountKey { index: 1 }],
+            false,
+            false
+        ).unwrap();
+        assert_eq!(metas[1], expected);
+        assert_eq!(ix.accounts[5].pubkey, Pubkey::find_program_address(&[MEMBERSHIP_SEED, mint.as_ref()], &crate::ID).0);
+    }
+
+    #[test]
+    fn full_whitelist_fits_at_the_400_byte_boundary() {
+        // Mọi danh sách đầy và mọi trường Option có giá trị: kích thước dữ liệu lớn nhất
+        let mut white_list = white_list();
+        white_list.pending_authority = Some(Pubkey::new_unique());
+        white_list.max_transfer_amount = Some(1);
+        white_list.admins = vec![Pubkey::new_unique(); WhiteList::MAX_ADMINS];
+        white_list.program_authority = Some(Pubkey::new_unique());
+        white_list.required_mint = Some(Pubkey::new_unique());
+        white_list.recent_removals = vec![RecentRemoval { account: Pubkey::new_unique(), slot: 0 }; WhiteList::MAX_RECENT_REMOVALS];
+        white_list.bypass_sender = Some(Pubkey::new_unique());
+        white_list.allowed_owner_programs = vec![Pubkey::new_unique(); WhiteList::MAX_OWNER_PROGRAMS];
+        let pending = PendingAddition { account: Pubkey::new_unique(), proposer: Pubkey::new_unique(), expires_at: Some(1) };
+        white_list.pending_additions = vec![pending; WhiteList::MAX_PENDING_ADDITIONS];
+        white_list.authority_pda = Some(AuthorityPda {
+            program_id: Pubkey::new_unique(),
+            seeds: vec![vec![0; AuthorityPda::MAX_SEED_LEN]; AuthorityPda::MAX_SEEDS],
+            bump: 255,
+        });
+        white_list.attestation_program = Some(Pubkey::new_unique());
+        white_list.attestation_expiry_offset = Some(0);
+        white_list.meta_list_authority = Some(Pubkey::new_unique());
+
+        let data_len = WhiteList::BASE_LEN + 400;
+        let capacity = white_list.capacity(data_len);
+        let mut added = 0;
+        loop {
+            let entry = WhiteListEntry { expires_at: Some(i64::MAX), ..entry(Pubkey::new_unique()) };
+            match white_list.push_entry(entry, capacity) {
+                Ok(()) => added += 1,
+                Err(error) => {
+                    assert!(is_error(Err(error), TransferError::WhitelistFull));
+                    break;
+                }
+            }
+        }
+        assert_eq!(added, 6);
+        // Ranh giới chính xác: thiếu một byte là mất một chỗ
+        assert_eq!(WhiteList::capacity_for(WhiteList::space_for(6) - 1), 5);
+        assert_eq!(WhiteList::capacity_for(WhiteList::space_for(7) - 1), 6);
+
+        // Whitelist đầy vẫn ghi vừa account, không bao giờ lỗi khi Anchor ghi dữ liệu
+        let mut data = vec![0u8; data_len];
+        white_list.try_serialize(&mut &mut data[..]).unwrap();
+        let used = white_list.try_to_vec().unwrap().len() + 8;
+        assert_eq!(used, WhiteList::space_for(6));
+    }
+
+    #[test]
+    fn suspended_address_is_blocked_until_unsuspended() {
+        let mut white_list = white_list();
+        let destination = Pubkey::new_unique();
+        white_list.add_entry(WhiteListEntry { tier: 1, added_at: 5, ..entry(destination) }, 10).unwrap();
+        let check = |white_list: &WhiteList| white_list.check_policy(&[], &destination, None, 1, 10);
+        assert!(check(&white_list).is_ok());
+
+        white_list.set_suspended(&destination, true).unwrap();
+        assert!(is_error(check(&white_list), TransferError::AddressSuspended));
+        assert!(is_error(white_list.require_member(&destination, 10), TransferError::NotWhitelisted));
+        // Dữ liệu của phần tử được giữ nguyên
+        let suspended = white_list.entry(&destination).unwrap();
+        assert_eq!((suspended.tier, suspended.added_at), (1, 5));
+
+        white_list.set_suspended(&destination, false).unwrap();
+        assert!(check(&white_list).is_ok());
+        assert!(is_error(white_list.set_suspended(&Pubkey::new_unique(), true), TransferError::AccountNotFound));
+    }
+
+    #[test]
+    fn burns_to_incinerator_skip_whitelist() {
+        let mut white_list = white_list();
+        white_list.initialize(Pubkey::new_unique(), 10, 0, 0);
+        assert!(white_list.allow_burn);
+        assert!(white_list.is_burn_exempt(&incinerator::ID));
+        assert!(!white_list.is_burn_exempt(&Pubkey::new_unique()));
+        // Incinerator không có trong whitelist nên không được miễn thì bị chặn
+        assert!(is_error(white_list.check_policy(&[], &incinerator::ID, None, 1, 0), TransferError::NotWhitelisted));
+
+        white_list.paused = true;
+        assert!(!white_list.is_burn_exempt(&incinerator::ID));
+        white_list.paused = false;
+        white_list.allow_burn = false;
+        assert!(!white_list.is_burn_exempt(&incinerator::ID));
+    }
+
+    #[test]
+    fn meta_list_manager_defaults_to_authority() {
+        let mut white_list = white_list();
+        white_list.authority = Pubkey::new_unique();
+        assert_eq!(white_list.meta_list_manager(), white_list.authority);
+
+        let manager = Pubkey::new_unique();
+        white_list.meta_list_authority = Some(manager);
+        assert_eq!(white_list.meta_list_manager(), manager);
+    }
+
+    // Thao tác trên whitelist cho kiểm thử thuộc tính, địa chỉ được chọn theo vị trí trong pool
+    #[derive(Clone, Copy, Debug)]
+    enum Op {
+        Add(usize),
+        Remove(usize),
+        Sort,
+    }
+
+    const POOL_SIZE: usize = 12;
+
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            2 => (0..POOL_SIZE).prop_map(Op::Add),
+            2 => (0..POOL_SIZE).prop_map(Op::Remove),
+            1 => Just(Op::Sort),
+        ]
+    }
+
+    proptest! {
+        // proptest thu gọn chuỗi thao tác gây lỗi về dạng ngắn nhất
+        #[test]
+        fn random_add_remove_sequences_match_model(ops in prop::collection::vec(op_strategy(), 0..200)) {
+            use std::collections::BTreeSet;
+
+            let pool: Vec<Pubkey> = (0..POOL_SIZE).map(|_| Pubkey::new_unique()).collect();
+            let capacity = 8;
+            let mut white_list = white_list();
+            // Mô hình tham chiếu: tập địa chỉ đã thêm và chưa bị xóa
+            let mut model = BTreeSet::new();
+            for op in ops {
+                match op {
+                    Op::Add(index) => {
+                        let account = pool[index];
+                        let result = white_list.add_entry(entry(account), capacity);
+                        if model.contains(&account) {
+                            prop_assert!(is_error(result, TransferError::AlreadyWhitelisted));
+                        } else if model.len() >= capacity {
+                            prop_assert!(is_error(result, TransferError::WhitelistFull));
+                        } else {
+                            prop_assert!(result.is_ok());
+                            model.insert(account);
+                        }
+                    }
+                    Op::Remove(index) => {
+                        let account = pool[index];
+                        let result = white_list.remove_account(&account, 0);
+                        if model.remove(&account) {
+                            prop_assert!(result.is_ok());
+                        } else {
+                            prop_assert!(is_error(result, TransferError::AccountNotFound));
+                        }
+                    }
+                    Op::Sort => prop_assert_eq!(white_list.sort_entries(), 0),
+                }
+
+                // Sắp xếp tăng dần nghiêm ngặt nên không có địa chỉ bị lặp
+                prop_assert!(white_list.is_sorted());
+                prop_assert!(white_list.white_list.len() <= capacity);
+                let accounts: Vec<Pubkey> = white_list.white_list.iter().map(|entry| entry.account).collect();
+                prop_assert_eq!(accounts, model.iter().copied().collect::<Vec<_>>());
+                prop_assert!(pool.iter().all(|account| white_list.contains(account) == model.contains(account)));
+            }
+        }
+    }
+
+    #[test]
+    fn remaining_capacity_counts_down_to_zero() {
+        let mut white_list = white_list();
+        let data_len = WhiteList::space_for(3);
+        assert_eq!(white_list.remaining_capacity(data_len), 3);
+        white_list.max_entries = 2;
+        assert_eq!(white_list.remaining_capacity(data_len), 2);
+
+        for _ in 0..2 {
+            white_list.add_entry(entry(Pubkey::new_unique()), white_list.capacity(data_len)).unwrap();
+        }
+        assert_eq!(white_list.remaining_capacity(data_len), 0);
+        // Giảm max_entries xuống dưới số địa chỉ hiện có vẫn trả về 0
+        white_list.max_entries = 1;
+        assert_eq!(white_list.remaining_capacity(data_len), 0);
+    }
+
+    #[test]
+    fn same_owner_only_blocks_cross_owner_transfers() {
+        let mut white_list = white_list();
+        white_list.same_owner_only = true;
+        let owner = Pubkey::new_unique();
+        // Ví không có trong whitelist vẫn được chuyển giữa các account của chính mình
+        assert!(!white_list.contains(&owner));
+        assert!(white_list.check_same_owner(&owner, &owner).is_ok());
+        assert!(is_error(white_list.check_same_owner(&owner, &Pubkey::new_unique()), TransferError::CrossOwnerTransferBlocked));
+
+        white_list.paused = true;
+        assert!(is_error(white_list.check_same_owner(&owner, &owner), TransferError::TransfersPaused));
+    }
+
+    #[test]
+    fn same_owner_only_keeps_amount_and_release_limits() {
+        let mut white_list = white_list();
+        white_list.same_owner_only = true;
+        white_list.check_source = true;
+        white_list.max_transfer_amount = Some(500);
+        let owner = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        // Chỉ kiểm tra thành viên được bỏ qua
+        assert!(white_list.check_policy(&[owner], &destination, None, 100, 0).is_ok());
+        assert!(is_error(white_list.check_policy(&[owner], &destination, None, 501, 0), TransferError::TransferAmountExceeded));
+        // Giới hạn theo cấp vẫn được áp dụng
+        white_list.tier1_max = 50;
+        white_list.activation_delay = 100;
+        white_list.white_list = vec![WhiteListEntry { tier: 1, ..entry(destination) }];
+        assert!(is_error(white_list.check_policy(&[owner], &destination, None, 100, 0), TransferError::TierLimitExceeded));
+        // Tạm ngưng, hết hạn và chưa kích hoạt được xử lý như nhau: đều bị bỏ qua
+        // giống như địa chỉ không có trong whitelist
+        assert!(white_list.check_policy(&[owner], &destination, None, 10, 0).is_ok());
+        white_list.white_list[0].suspended = true;
+        assert!(white_list.check_policy(&[owner], &destination, None, 10, 0).is_ok());
+        white_list.white_list[0].expires_at = Some(1);
+        assert!(white_list.check_policy(&[owner], &destination, None, 10, 200).is_ok());
+        // Tắt chế độ thì phần tử bị tạm ngưng lại bị chặn
+        white_list.same_owner_only = false;
+        assert!(is_error(white_list.check_policy(&[destination], &destination, None, 10, 200), TransferError::AddressSuspended));
+
+        // Escrow có lịch giải phóng: chuyển sang account khác của cùng ví vẫn bị giới hạn
+        let mut schedule = ReleaseSchedule {
+            escrow: Pubkey::new_unique(),
+            start_ts: 0,
+            cliff: 100,
+            duration: 1_000,
+            total_deposited: 1_000,
+            withdrawn: 0,
+        };
+        assert!(is_error(schedule.withdraw(1, 50), TransferError::AmountNotYetReleased));
+        schedule.withdraw(500, 500).unwrap();
+        assert!(is_error(schedule.withdraw(1, 500), TransferError::AmountNotYetReleased));
+        assert_eq!(schedule.withdrawn, 500);
+    }
+
+    #[test]
+    fn migrate_inserts_version_into_legacy_layout() {
+        let mut white_list = white_list();
+        white_list.white_list = vec![entry(Pubkey::new_unique()), entry(Pubkey::new_unique())];
+        let mut current = Vec::new();
+        white_list.try_serialize(&mut current).unwrap();
+        let authority = white_list.authority;
+        assert_eq!(WhiteList::stored_version(&current, &authority).unwrap(), WhiteList::VERSION);
+
+        // Layout phiên bản 0: không có byte version sau discriminator
+        let mut legacy = current.clone();
+        legacy.remove(8);
+        assert_eq!(WhiteList::stored_version(&legacy, &authority).unwrap(), 0);
+        assert!(is_error(WhiteList::stored_version(&legacy, &Pubkey::new_unique()).map(|_| ()), TransferError::Unauthorized));
+
+        let old_len = legacy.len();
+        legacy.push(0);
+        WhiteList::insert_version(&mut legacy, old_len);
+        assert_eq!(legacy, current);
+        let migrated = WhiteList::try_deserialize(&mut &legacy[..]).unwrap();
+        assert_eq!(migrated.version, WhiteList::VERSION);
+        assert_eq!(migrated.white_list, white_list.white_list);
+    }
+
+    #[test]
+    fn fee_is_recorded_in_basis_points() {
+        let mut white_list = white_list();
+        // 0 basis point: tắt phí
+        assert_eq!(white_list.fee_for(1_000_000).unwrap(), 0);
+        // Mẫu số 0 của whitelist cũ được hiểu là basis point
+        white_list.fee_numerator = 25;
+        assert_eq!(white_list.fee_for(1_000_000).unwrap(), 2_500);
+        // Làm tròn xuống, giao dịch nhỏ không có phí
+        assert_eq!(white_list.fee_for(399).unwrap(), 0);
+        white_list.fee_numerator = WhiteList::MAX_FEE_BASIS_POINTS as u32;
+        assert_eq!(white_list.fee_for(u64::MAX).unwrap(), u64::MAX / 10);
+
+        let mut treasury = Treasury { mint: Pubkey::new_unique(), accrued_fees: 0 };
+        treasury.accrue(2_500).unwrap();
+        treasury.accrue(10).unwrap();
+        assert_eq!(treasury.accrued_fees, 2_510);
+        assert!(is_error(treasury.accrue(u64::MAX), TransferError::AmountOverflow));
+    }
+
+    #[test]
+    fn fee_denominator_is_configurable() {
+        let mut white_list = white_list();
+        // 2.5 phần triệu, nhỏ hơn 1 basis point
+        white_list.fee_numerator = 25;
+        white_list.fee_denominator = 10_000_000;
+        assert_eq!(white_list.fee_denominator(), 10_000_000);
+        assert_eq!(white_list.fee_for(1_000_000).unwrap(), 2);
+        white_list.fee_numerator = u32::MAX;
+        white_list.fee_denominator = 1;
+        assert!(white_list.fee_for(u64::MAX).is_err());
+
+        assert!(WhiteList::validate_fee(25, 1_000_000).is_ok());
+        assert!(WhiteList::validate_fee(0, WhiteList::FEE_DENOMINATOR).is_ok());
+        assert!(WhiteList::validate_fee(100_000, 1_000_000).is_ok());
+        assert!(is_error(WhiteList::validate_fee(100_001, 1_000_000), TransferError::FeeTooHigh));
+        assert!(is_error(WhiteList::validate_fee(1, 0), TransferError::InvalidFeeRate));
+        assert!(is_error(WhiteList::validate_fee(11, 10), TransferError::InvalidFeeRate));
+    }
+
+    #[test]
+    fn migrate_adds_fee_fields_to_version_1_layout() {
+        let mut white_list = white_list();
+        white_list.white_list = vec![entry(Pubkey::new_unique()), entry(Pubkey::new_unique())];
+        white_list.same_owner_only = true;
+        let mut current = Vec::new();
+        white_list.try_serialize(&mut current).unwrap();
+        let authority = white_list.authority;
+
+        // Layout phiên bản 1: không có fee_numerator và fee_denominator ở cuối,
+        // account có đúng BASE_LEN cũ nên không còn chỗ cho hai trường này
+        let mut legacy = current.clone();
+        legacy.truncate(current.len() - 8);
+        legacy[8] = 1;
+        assert_eq!(WhiteList::stored_version(&legacy, &authority).unwrap(), 1);
+
+        // Phần tăng thêm của account có thể còn byte cũ (ví dụ sau khi xóa địa chỉ)
+        let old_len = legacy.len();
+        legacy.extend_from_slice(&[0xff; 8]);
+        let migrated = WhiteList::upgrade_layout(&mut legacy, 1, old_len).unwrap();
+        assert_eq!(legacy[8], WhiteList::VERSION);
+        assert_eq!(migrated.version, WhiteList::VERSION);
+        assert_eq!(migrated.white_list, white_list.white_list);
+        assert!(migrated.same_owner_only);
+        assert_eq!(migrated.fee_numerator, 0);
+        assert_eq!(migrated.fee_denominator, WhiteList::FEE_DENOMINATOR);
+        // Phiên bản không xác định không được chuyển đổi
+        assert!(WhiteList::upgrade_layout(&mut legacy, 3, old_len).is_err());
+    }
+
+    #[test]
+    fn health_check_reports_invalid_fee_rate() {
+        let mut white_list = white_list();
+        let data_len = WhiteList::space_for(10);
+        white_list.fee_numerator = WhiteList::MAX_FEE_BASIS_POINTS as u32;
+        assert!(white_list.config_violations(data_len).is_empty());
+        // Vượt quá 10%
+        white_list.fee_numerator += 1;
+        assert_eq!(white_list.config_violations(data_len), vec![ConfigViolation::InvalidFeeRate]);
+        // Tử số lớn hơn mẫu số
+        white_list.fee_numerator = 2;
+        white_list.fee_denominator = 1;
+        assert_eq!(white_list.config_violations(data_len), vec![ConfigViolation::InvalidFeeRate]);
+    }
+}
@@ -1,6 +1,6 @@
 /**
  * Bài test này kiểm tra chức năng Transfer Hook Whitelist
- * Mục đích: Chỉ cho phép các account token có trong whitelist mới có thể nhận được token
+ * Mục đích: Chỉ cho phép các account token thuộc về ví có trong whitelist mới có thể nhận được token
  */
 
 // Import các thư viện và module cần thiết
@@ -11,6 +11,7 @@ import {
   ExtensionType,                // Enum cho các loại extension của token
   TOKEN_2022_PROGRAM_ID,        // ID của Token-2022 Program
   createAssociatedTokenAccountInstruction,  // Tạo account token liên kết
+  createMint,                               // Tạo mint token tiêu chuẩn
   createInitializeMintInstruction,          // Khởi tạo mint token
   createInitializeTransferHookInstruction,   // Khởi tạo transfer hook cho mint
   createMintToInstruction,                  // Mint token vào account
@@ -19,6 +20,7 @@ import {
   getMintLen,                     // Tính kích thước account mint
 } from '@solana/spl-token';
 import { Keypair, SystemProgram, Transaction, sendAndConfirmTransaction } from '@solana/web3.js';
+import { expect } from 'chai';
 import type { TransferHook } from '../target/types/transfer_hook';
 
 describe('transfer-hook', () => {
@@ -33,6 +35,26 @@ describe('transfer-hook', () => {
   const wallet = provider.wallet as anchor.Wallet;  // Ví dùng để ký giao dịch
   const connection = provider.connection;          // Kết nối đến Solana cluster
 
+  /**
+   * Gửi transaction và kiểm tra nó thất bại với mã lỗi `errorName` của program
+   * Với skipPreflight, lỗi của program nằm trong thông báo dạng {"Custom":<code>}
+   */
+  const expectProgramError = async (transaction: Transaction, errorName: string) => {
+    // So sánh không phân biệt hoa thường vì tên lỗi trong IDL có thể được chuyển sang camelCase
+    const code = program.idl.errors?.find((e) => e.name.toLowerCase() === errorName.toLowerCase())?.code;
+    expect(code, `unknown error ${errorName}`).to.not.be.undefined;
+
+    let failed = false;
+    try {
+      await sendAndConfirmTransaction(connection, transaction, [wallet.payer], { skipPreflight: true });
+    } catch (error: any) {
+      failed = true;
+      console.log(`Expected Error (${errorName}):`, error.message);
+      expect(error.message).to.include(`"Custom":${code}`);
+    }
+    expect(failed, 'transaction should have failed but succeeded').to.be.true;
+  };
+
   // Tạo keypair mới để dùng làm địa chỉ cho mint token với transfer hook
   const mint = new Keypair();
   const decimals = 9;  // Số thập phân của token (10^9 = 1 token)
@@ -190,7 +212,7 @@ describe('transfer-hook', () => {
   it('Create ExtraAccountMetaList Account', async () => {
     // Tạo instruction gọi hàm initializeExtraAccountMetaList từ program
     const initializeExtraAccountMetaListInstruction = await program.methods
-      .initializeExtraAccountMetaList()   // Gọi hàm initializeExtraAccountMetaList trong contract
+      .initializeExtraAccountMetaList(10) // Gọi hàm initializeExtraAccountMetaList, whitelist chứa tối đa 10 địa chỉ
       .accounts({
         mint: mint.publicKey,             // Truyền account mint
         // Các account khác như payer, extra_account_meta_list, system_program
@@ -213,15 +235,16 @@ describe('transfer-hook', () => {
   // TEST CASE 4: Thêm account vào whitelist
   // ======================================================================
   /**
-   * Thêm destinationTokenAccount vào whitelist để cho phép
-   * chuyển token đến account này.
+   * Thêm ví recipient (chủ sở hữu của destinationTokenAccount) vào whitelist
+   * để cho phép chuyển token đến các account token của ví này.
    */
   it('Add account to white list', async () => {
     // Tạo instruction gọi hàm addToWhitelist từ program
     const addAccountToWhiteListInstruction = await program.methods
       .addToWhitelist()         // Gọi hàm addToWhitelist trong contract
       .accounts({
-        newAccount: destinationTokenAccount,  // account cần thêm vào whitelist
+        mint: mint.publicKey,                 // Mint có whitelist cần cập nhật
+        newAccount: recipient.publicKey,      // Ví cần thêm vào whitelist
         signer: wallet.publicKey,             // Người ký (phải là authority của whitelist)
         // white_list được tự động thêm vào từ Anchor context
       })
@@ -234,6 +257,124 @@ describe('transfer-hook', () => {
     console.log('White Listed:', txSig);
   });
 
+  // ======================================================================
+  // TEST CASE 4b: Kiểm tra tính nhất quán của cấu hình
+  // ======================================================================
+  /**
+   * health_check trả về danh sách vi phạm qua return data.
+   * Sau khi khởi tạo và thêm một địa chỉ, cấu hình phải hợp lệ (danh sách rỗng).
+   */
+  it('Health check reports no violations', async () => {
+    // view() mô phỏng transaction và giải mã return data
+    const violations = await program.methods.healthCheck().accounts({ mint: mint.publicKey }).view();
+
+    expect(violations).to.deep.equal([]);
+  });
+
+  // ======================================================================
+  // TEST CASE 4c: Kiểm tra một địa chỉ có trong whitelist
+  // ======================================================================
+  /**
+   * is_whitelisted trả về bool qua return data mà không thay đổi trạng thái.
+   */
+  it('Check whether accounts are whitelisted', async () => {
+    const whitelisted = await program.methods
+      .isWhitelisted(recipient.publicKey)
+      .accounts({ mint: mint.publicKey })
+      .view();
+    const notWhitelisted = await program.methods
+      .isWhitelisted(nonWhitelistedRecipient.publicKey)
+      .accounts({ mint: mint.publicKey })
+      .view();
+
+    expect(whitelisted).to.equal(true);
+    expect(notWhitelisted).to.equal(false);
+  });
+
+  // ======================================================================
+  // TEST CASE 4d: Thêm nhiều địa chỉ vào whitelist trong một giao dịch
+  // ======================================================================
+  /**
+   * Địa chỉ đã có (recipient) sẽ được bỏ qua, chỉ các địa chỉ mới được thêm.
+   */
+  it('Add many accounts to white list', async () => {
+    const newAccounts = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+
+    await program.methods
+      .addManyToWhitelist([recipient.publicKey, ...newAccounts])
+      .accounts({ mint: mint.publicKey, signer: wallet.publicKey })
+      .rpc();
+
+    for (const account of newAccounts) {
+      const whitelisted = await program.methods.isWhitelisted(account).accounts({ mint: mint.publicKey }).view();
+      expect(whitelisted).to.equal(true);
+    }
+  });
+
+  // ======================================================================
+  // TEST CASE 4e: Admin được ủy quyền quản lý whitelist
+  // ======================================================================
+  /**
+   * authority thêm một admin, admin này có thể thêm địa chỉ vào whitelist
+   * mà không cần khóa của authority. Sau đó admin bị thu hồi quyền.
+   */
+  it('Admin can manage white list', async () => {
+    const admin = Keypair.generate();
+    const newAccount = Keypair.generate().publicKey;
+
+    await program.methods
+      .addAdmin(admin.publicKey)
+      .accounts({ mint: mint.publicKey, signer: wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .addManyToWhitelist([newAccount])
+      .accounts({ mint: mint.publicKey, signer: admin.publicKey })
+      .signers([admin])
+      .rpc();
+    const whitelisted = await program.methods.isWhitelisted(newAccount).accounts({ mint: mint.publicKey }).view();
+    expect(whitelisted).to.equal(true);
+
+    await program.methods
+      .removeAdmin(admin.publicKey)
+      .accounts({ mint: mint.publicKey, signer: wallet.publicKey })
+      .rpc();
+
+    // Admin đã bị thu hồi quyền không thể thay đổi whitelist nữa
+    let failed = false;
+    try {
+      await program.methods
+        .removeManyFromWhitelist([newAccount])
+        .accounts({ mint: mint.publicKey, signer: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    } catch (error) {
+      failed = true;
+    }
+    expect(failed).to.equal(true);
+  });
+
+  // ======================================================================
+  // TEST CASE 4f: Kiểm tra trước một giao dịch chuyển token
+  // ======================================================================
+  /**
+   * can_transfer áp dụng các chính sách của whitelist mà không cần account token.
+   */
+  it('Check whether transfers would succeed', async () => {
+    const amount = new anchor.BN(1 * 10 ** decimals);
+    const allowed = await program.methods
+      .canTransfer(wallet.publicKey, recipient.publicKey, amount)
+      .accounts({ mint: mint.publicKey })
+      .view();
+    const rejected = await program.methods
+      .canTransfer(wallet.publicKey, nonWhitelistedRecipient.publicKey, amount)
+      .accounts({ mint: mint.publicKey })
+      .view();
+
+    expect(allowed).to.equal(true);
+    expect(rejected).to.equal(false);
+  });
+
   // ======================================================================
   // TEST CASE 5: Chuyển token đến account có trong whitelist
   // ======================================================================
@@ -269,11 +410,103 @@ describe('transfer-hook', () => {
     console.log('Transfer to Whitelisted Account Succeeded:', txSig);
   });
 
+  // ======================================================================
+  // TEST CASE 5b: Yêu cầu nắm giữ token thành viên
+  // ======================================================================
+  /**
+   * Khi cấu hình yêu cầu token thành viên, người nhận chưa nắm giữ token đó
+   * sẽ không nhận được token dù đã có trong whitelist.
+   * Sau test, yêu cầu được tắt (required_balance = 0) để không ảnh hưởng các test sau.
+   */
+  it('Transfer Hook with Membership Requirement - Recipient Without Membership (Should Fail)', async () => {
+    // Tạo mint token thành viên, người nhận chưa có account token của mint này
+    const membershipMint = await createMint(connection, wallet.payer, wallet.publicKey, null, 0);
+
+    await program.methods
+      .setMembershipRequirement(new anchor.BN(1))
+      .accounts({
+        mint: mint.publicKey,
+        requiredMint: membershipMint,
+        signer: wallet.publicKey,
+      })
+      .rpc();
+
+    const transferInstruction = await createTransferCheckedWithTransferHookInstruction(
+      connection,
+      sourceTokenAccount,
+      mint.publicKey,
+      destinationTokenAccount,    // account có trong whitelist nhưng chưa có token thành viên
+      wallet.publicKey,
+      BigInt(1 * 10 ** decimals),
+      decimals,
+      [],
+      'confirmed',
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const transaction = new Transaction().add(transferInstruction);
+
+    let failed = false;
+    try {
+      await sendAndConfirmTransaction(connection, transaction, [wallet.payer], { skipPreflight: true });
+    } catch (error: any) {
+      failed = true;
+      console.log('Expected Error - Recipient Without Membership Failed:', error.message);
+    }
+    expect(failed).to.equal(true);
+
+    // Tắt yêu cầu token thành viên
+    await program.methods
+      .setMembershipRequirement(new anchor.BN(0))
+      .accounts({
+        mint: mint.publicKey,
+        requiredMint: membershipMint,
+        signer: wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  // ======================================================================
+  // TEST CASE 5c: Tạm dừng toàn bộ giao dịch chuyển token
+  // ======================================================================
+  /**
+   * Khi bật tạm dừng, chuyển token đến account có trong whitelist cũng thất bại.
+   * Sau test, tạm dừng được tắt để không ảnh hưởng các test sau.
+   */
+  it('Transfer Hook while Paused (Should Fail)', async () => {
+    await program.methods.setPaused(true).accounts({ mint: mint.publicKey, signer: wallet.publicKey }).rpc();
+
+    const transferInstruction = await createTransferCheckedWithTransferHookInstruction(
+      connection,
+      sourceTokenAccount,
+      mint.publicKey,
+      destinationTokenAccount,    // account có trong whitelist
+      wallet.publicKey,
+      BigInt(1 * 10 ** decimals),
+      decimals,
+      [],
+      'confirmed',
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const transaction = new Transaction().add(transferInstruction);
+
+    let failed = false;
+    try {
+      await sendAndConfirmTransaction(connection, transaction, [wallet.payer], { skipPreflight: true });
+    } catch (error: any) {
+      failed = true;
+      console.log('Expected Error - Transfer while Paused Failed:', error.message);
+    }
+    expect(failed).to.equal(true);
+
+    // Tiếp tục cho phép chuyển token
+    await program.methods.setPaused(false).accounts({ mint: mint.publicKey, signer: wallet.publicKey }).rpc();
+  });
+
   // ======================================================================
   // TEST CASE 6: Xóa account khỏi whitelist
   // ======================================================================
   /**
-   * Xóa destinationTokenAccount khỏi whitelist để chuẩn bị cho
+   * Xóa ví recipient khỏi whitelist để chuẩn bị cho
    * test case tiếp theo (chuyển token đến account đã bị xóa khỏi whitelist).
    */
   it('Remove account from white list', async () => {
@@ -281,7 +514,8 @@ describe('transfer-hook', () => {
     const removeFromWhiteListInstruction = await program.methods
       .removeFromWhitelist()     // Gọi hàm removeFromWhitelist trong contract
       .accounts({
-        accountToRemove: destinationTokenAccount,  // account cần xóa
+        mint: mint.publicKey,                     // Mint có whitelist cần cập nhật
+        accountToRemove: recipient.publicKey,     // Ví cần xóa
         signer: wallet.publicKey,                 // Người ký (phải là authority)
         // white_list được tự động thêm vào từ Anchor context
       })
@@ -324,15 +558,8 @@ describe('transfer-hook', () => {
     // Tạo và gửi transaction
     const transaction = new Transaction().add(transferInstruction);
 
-    // Bọc trong try-catch vì chúng ta kỳ vọng transaction sẽ thất bại
-    try {
-      await sendAndConfirmTransaction(connection, transaction, [wallet.payer], { skipPreflight: true });
-      console.log('ERROR: Transfer to Removed Account Unexpectedly Succeeded');
-      throw new Error('Transfer to removed account should have failed but succeeded');
-    } catch (error: any) {
-      // Báo lỗi đúng như kỳ vọng
-      console.log('Expected Error - Transfer to Removed Account Failed:', error.message);
-    }
+    // Transfer đi qua Token-2022 và gọi hook, hook phải từ chối với lỗi NotWhitelisted
+    await expectProgramError(transaction, 'NotWhitelisted');
   });
 
   // ======================================================================
@@ -365,14 +592,7 @@ describe('transfer-hook', () => {
     // Tạo và gửi transaction
     const transaction = new Transaction().add(transferInstruction);
 
-    // Bọc trong try-catch vì chúng ta kỳ vọng transaction sẽ thất bại
-    try {
-      await sendAndConfirmTransaction(connection, transaction, [wallet.payer], { skipPreflight: true });
-      console.log('ERROR: Transfer to Non-Whitelisted Account Unexpectedly Succeeded');
-      throw new Error('Transfer to non-whitelisted account should have failed but succeeded');
-    } catch (error: any) {
-      // Báo lỗi đúng như kỳ vọng
-      console.log('Expected Error - Transfer to Non-Whitelisted Account Failed:', error.message);
-    }
+    // Transfer đi qua Token-2022 và gọi hook, hook phải từ chối với lỗi NotWhitelisted
+    await expectProgramError(transaction, 'NotWhitelisted');
   });
 });